@@ -0,0 +1,47 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BitMask {
+    Mask(i64),
+    Positions(Vec<u32>),
+}
+
+impl From<i32> for BitMask {
+    fn from(mask: i32) -> Self {
+        BitMask::Mask(mask.into())
+    }
+}
+
+impl From<i64> for BitMask {
+    fn from(mask: i64) -> Self {
+        BitMask::Mask(mask)
+    }
+}
+
+impl From<Vec<u32>> for BitMask {
+    fn from(positions: Vec<u32>) -> Self {
+        BitMask::Positions(positions)
+    }
+}
+
+impl From<&[u32]> for BitMask {
+    fn from(positions: &[u32]) -> Self {
+        BitMask::Positions(positions.to_vec())
+    }
+}
+
+impl<const N: usize> From<[u32; N]> for BitMask {
+    fn from(positions: [u32; N]) -> Self {
+        BitMask::Positions(positions.to_vec())
+    }
+}
+
+impl From<BitMask> for bson::Bson {
+    fn from(mask: BitMask) -> Self {
+        match mask {
+            BitMask::Mask(mask) => bson::Bson::Int64(mask),
+            BitMask::Positions(positions) => positions
+                .into_iter()
+                .map(|position| bson::Bson::Int64(position.into()))
+                .collect(),
+        }
+    }
+}
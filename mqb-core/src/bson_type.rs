@@ -0,0 +1,58 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BsonType {
+    Double,
+    String,
+    Object,
+    Array,
+    BinData,
+    Undefined,
+    ObjectId,
+    Bool,
+    Date,
+    Null,
+    Regex,
+    DbPointer,
+    JavaScript,
+    Symbol,
+    Int,
+    Timestamp,
+    Long,
+    Decimal,
+    MinKey,
+    MaxKey,
+    Number,
+}
+
+impl BsonType {
+    pub fn alias(&self) -> &'static str {
+        match self {
+            BsonType::Double => "double",
+            BsonType::String => "string",
+            BsonType::Object => "object",
+            BsonType::Array => "array",
+            BsonType::BinData => "binData",
+            BsonType::Undefined => "undefined",
+            BsonType::ObjectId => "objectId",
+            BsonType::Bool => "bool",
+            BsonType::Date => "date",
+            BsonType::Null => "null",
+            BsonType::Regex => "regex",
+            BsonType::DbPointer => "dbPointer",
+            BsonType::JavaScript => "javascript",
+            BsonType::Symbol => "symbol",
+            BsonType::Int => "int",
+            BsonType::Timestamp => "timestamp",
+            BsonType::Long => "long",
+            BsonType::Decimal => "decimal",
+            BsonType::MinKey => "minKey",
+            BsonType::MaxKey => "maxKey",
+            BsonType::Number => "number",
+        }
+    }
+}
+
+impl From<BsonType> for bson::Bson {
+    fn from(ty: BsonType) -> Self {
+        bson::Bson::String(ty.alias().to_owned())
+    }
+}
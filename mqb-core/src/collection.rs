@@ -0,0 +1,188 @@
+use std::borrow::Borrow;
+
+use mongodb::{
+    options::UpdateOptions,
+    results::{DeleteResult, InsertManyResult, InsertOneResult, UpdateResult},
+    Collection, Cursor,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{kp::KeyPathableAsRoot, FilterBuilder, UpdateBuilder};
+
+#[derive(Debug)]
+pub enum CollectionError {
+    Build(crate::Errors),
+    Driver(mongodb::error::Error),
+}
+
+impl std::fmt::Display for CollectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollectionError::Build(e) => write!(f, "failed to build: {}", e),
+            CollectionError::Driver(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CollectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CollectionError::Build(e) => Some(e),
+            CollectionError::Driver(e) => Some(e),
+        }
+    }
+}
+
+impl From<mongodb::error::Error> for CollectionError {
+    fn from(e: mongodb::error::Error) -> Self {
+        CollectionError::Driver(e)
+    }
+}
+
+// Only builders rooted at `T` are accepted, so a filter written for one
+// type can't be sent to a collection of another.
+#[derive(Clone, Debug)]
+pub struct TypedCollection<T: Send + Sync> {
+    collection: Collection<T>,
+}
+
+impl<T> TypedCollection<T>
+where
+    T: KeyPathableAsRoot + Serialize + DeserializeOwned + Send + Sync,
+{
+    pub fn new(collection: Collection<T>) -> Self {
+        Self { collection }
+    }
+
+    pub fn inner(&self) -> &Collection<T> {
+        &self.collection
+    }
+
+    pub async fn find(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<Cursor<T>, CollectionError> {
+        let filter = filter.try_build().map_err(CollectionError::Build)?;
+        Ok(self.collection.find(filter).await?)
+    }
+
+    pub async fn find_one(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<Option<T>, CollectionError> {
+        let filter = filter.try_build().map_err(CollectionError::Build)?;
+        Ok(self.collection.find_one(filter).await?)
+    }
+
+    pub async fn count(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<u64, CollectionError> {
+        let filter = filter.try_build().map_err(CollectionError::Build)?;
+        Ok(self.collection.count_documents(filter).await?)
+    }
+
+    pub async fn insert_one(
+        &self,
+        value: impl Borrow<T>,
+    ) -> Result<InsertOneResult, CollectionError> {
+        Ok(self.collection.insert_one(value).await?)
+    }
+
+    pub async fn insert_many(
+        &self,
+        values: impl IntoIterator<Item = impl Borrow<T>>,
+    ) -> Result<InsertManyResult, CollectionError> {
+        Ok(self.collection.insert_many(values).await?)
+    }
+
+    pub async fn update_one(
+        &self,
+        filter: FilterBuilder<T>,
+        update: UpdateBuilder<T>,
+    ) -> Result<UpdateResult, CollectionError> {
+        let filter = filter.try_build().map_err(CollectionError::Build)?;
+        let (update, options) = update
+            .try_build_with_options()
+            .map_err(CollectionError::Build)?;
+        Ok(self
+            .collection
+            .update_one(filter, update)
+            .with_options(options)
+            .await?)
+    }
+
+    pub async fn update_many(
+        &self,
+        filter: FilterBuilder<T>,
+        update: UpdateBuilder<T>,
+    ) -> Result<UpdateResult, CollectionError> {
+        let filter = filter.try_build().map_err(CollectionError::Build)?;
+        let (update, options) = update
+            .try_build_with_options()
+            .map_err(CollectionError::Build)?;
+        Ok(self
+            .collection
+            .update_many(filter, update)
+            .with_options(options)
+            .await?)
+    }
+
+    pub async fn upsert(
+        &self,
+        filter: FilterBuilder<T>,
+        update: UpdateBuilder<T>,
+    ) -> Result<UpdateResult, CollectionError> {
+        let filter = filter.try_build().map_err(CollectionError::Build)?;
+        let (update, options) = update
+            .try_build_with_options()
+            .map_err(CollectionError::Build)?;
+        Ok(self
+            .collection
+            .update_one(filter, update)
+            .with_options(options)
+            .upsert(true)
+            .await?)
+    }
+
+    pub async fn delete_one(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<DeleteResult, CollectionError> {
+        let filter = filter.try_build().map_err(CollectionError::Build)?;
+        Ok(self.collection.delete_one(filter).await?)
+    }
+
+    pub async fn delete_many(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<DeleteResult, CollectionError> {
+        let filter = filter.try_build().map_err(CollectionError::Build)?;
+        Ok(self.collection.delete_many(filter).await?)
+    }
+}
+
+impl<T> From<Collection<T>> for TypedCollection<T>
+where
+    T: KeyPathableAsRoot + Serialize + DeserializeOwned + Send + Sync,
+{
+    fn from(collection: Collection<T>) -> Self {
+        Self::new(collection)
+    }
+}
+
+impl<T> UpdateBuilder<T> {
+    // Builds the update together with the driver options it needs. Array
+    // filters are only sent when the update uses them.
+    pub fn try_build_with_options(
+        self,
+    ) -> Result<(bson::Document, UpdateOptions), crate::Errors> {
+        let (update, array_filters) = self.try_build_with_array_filters()?;
+
+        let options = UpdateOptions::builder()
+            .array_filters((!array_filters.is_empty()).then_some(array_filters))
+            .build();
+
+        Ok((update, options))
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{kp::SerializeFn, Error, Errors};
+
+// Conditions on the elements of an array of scalars, which have no key
+// paths of their own: `$elemMatch`, `$pull` and array filters apply the
+// operators to each element directly.
+pub struct ElementFilter<V> {
+    document: bson::Document,
+    errors: Vec<Error>,
+    serializer: SerializeFn<Vec<V>>,
+}
+
+impl<V> ElementFilter<V> {
+    pub(crate) fn new(serializer: SerializeFn<Vec<V>>) -> Self {
+        Self {
+            document: bson::Document::new(),
+            errors: Vec::new(),
+            serializer,
+        }
+    }
+
+    pub(crate) fn try_build(self) -> Result<bson::Document, Errors> {
+        Errors::check(self.errors, self.document)
+    }
+
+    pub fn eq(self, value: impl Into<V>) -> Self
+    where
+        V: PartialEq,
+    {
+        self.op(value.into(), "$eq")
+    }
+
+    pub fn ne(self, value: impl Into<V>) -> Self
+    where
+        V: PartialEq,
+    {
+        self.op(value.into(), "$ne")
+    }
+
+    pub fn gt(self, value: impl Into<V>) -> Self
+    where
+        V: PartialOrd,
+    {
+        self.op(value.into(), "$gt")
+    }
+
+    pub fn gte(self, value: impl Into<V>) -> Self
+    where
+        V: PartialOrd,
+    {
+        self.op(value.into(), "$gte")
+    }
+
+    pub fn lt(self, value: impl Into<V>) -> Self
+    where
+        V: PartialOrd,
+    {
+        self.op(value.into(), "$lt")
+    }
+
+    pub fn lte(self, value: impl Into<V>) -> Self
+    where
+        V: PartialOrd,
+    {
+        self.op(value.into(), "$lte")
+    }
+
+    pub fn r#in(self, values: impl IntoIterator<Item = impl Into<V>>) -> Self {
+        self.values_op(values, "$in")
+    }
+
+    pub fn nin(self, values: impl IntoIterator<Item = impl Into<V>>) -> Self {
+        self.values_op(values, "$nin")
+    }
+}
+
+impl<V> ElementFilter<V> {
+    fn op(mut self, value: V, op: &'static str) -> Self {
+        let Some(elements) = self.serialize(vec![value], op) else {
+            return self;
+        };
+
+        match elements.into_iter().next() {
+            Some(bson) => self.insert(op, bson),
+            None => {
+                self.errors.push(Error::invalid(
+                    None,
+                    op,
+                    "the value serialized to an empty array",
+                ));
+                self
+            }
+        }
+    }
+
+    fn values_op(
+        mut self,
+        values: impl IntoIterator<Item = impl Into<V>>,
+        op: &'static str,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+
+        match self.serialize(values, op) {
+            Some(elements) => self.insert(op, elements.into()),
+            None => self,
+        }
+    }
+
+    // Elements go through the array's own serializer, so a `serialize_with`
+    // on the field applies to them as well.
+    fn serialize(
+        &mut self,
+        values: Vec<V>,
+        op: &str,
+    ) -> Option<Vec<bson::Bson>> {
+        let error = match (self.serializer)(&values) {
+            Ok(bson::Bson::Array(elements)) => return Some(elements),
+            Ok(other) => Error::invalid(
+                None,
+                op,
+                format!("expected an array, got {:?}", other.element_type()),
+            ),
+            Err(e) => Error::serialization(None, op, e),
+        };
+
+        self.errors.push(error);
+        None
+    }
+
+    // There is no `$and` to fall back to for the element itself, so a
+    // repeated operator is always reported.
+    fn insert(mut self, op: &'static str, bson: bson::Bson) -> Self {
+        if self.document.contains_key(op) {
+            self.errors.push(Error::duplicate(None, op));
+            return self;
+        }
+
+        self.document.insert(op, bson);
+        self
+    }
+}
@@ -0,0 +1,180 @@
+#[derive(Debug)]
+pub enum ErrorCause {
+    Serialization(bson::ser::Error),
+    Invalid(String),
+    Conflict { path: String, operator: String },
+    Duplicate,
+}
+
+impl std::fmt::Display for ErrorCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorCause::Serialization(e) => write!(f, "{}", e),
+            ErrorCause::Invalid(message) => write!(f, "{}", message),
+            ErrorCause::Conflict { path, operator } => {
+                write!(f, "conflicts with {} {}", path, operator)
+            }
+            ErrorCause::Duplicate => write!(f, "duplicate operator"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Error {
+    path: Option<String>,
+    operator: String,
+    cause: ErrorCause,
+}
+
+impl Error {
+    pub(crate) fn serialization(
+        path: Option<String>,
+        operator: &str,
+        error: bson::ser::Error,
+    ) -> Self {
+        Self {
+            path,
+            operator: operator.to_owned(),
+            cause: ErrorCause::Serialization(error),
+        }
+    }
+
+    pub(crate) fn invalid(
+        path: Option<String>,
+        operator: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            path,
+            operator: operator.to_owned(),
+            cause: ErrorCause::Invalid(message.into()),
+        }
+    }
+
+    pub(crate) fn conflict(
+        path: &str,
+        operator: &str,
+        other_path: &str,
+        other_operator: &str,
+    ) -> Self {
+        Self {
+            path: Some(path.to_owned()),
+            operator: operator.to_owned(),
+            cause: ErrorCause::Conflict {
+                path: other_path.to_owned(),
+                operator: other_operator.to_owned(),
+            },
+        }
+    }
+
+    pub(crate) fn duplicate(path: Option<String>, operator: &str) -> Self {
+        Self {
+            path,
+            operator: operator.to_owned(),
+            cause: ErrorCause::Duplicate,
+        }
+    }
+
+    // Errors from a builder nested under a key path, such as `$elemMatch`,
+    // are reported relative to the outer path.
+    pub(crate) fn within(mut self, parent: &str) -> Self {
+        self.path = Some(match self.path {
+            Some(path) => format!("{}.{}", parent, path),
+            None => parent.to_owned(),
+        });
+        self
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    pub fn operator(&self) -> &str {
+        &self.operator
+    }
+
+    pub fn cause(&self) -> &ErrorCause {
+        &self.cause
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => {
+                write!(f, "{} {}: {}", path, self.operator, self.cause)
+            }
+            None => write!(f, "{}: {}", self.operator, self.cause),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.cause {
+            ErrorCause::Serialization(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// Builders keep going after a failure, so every error is reported rather
+// than only the last one.
+#[derive(Debug)]
+pub struct Errors(pub(crate) Vec<Error>);
+
+impl Errors {
+    pub(crate) fn check<V>(errors: Vec<Error>, value: V) -> Result<V, Self> {
+        if errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(Self(errors))
+        }
+    }
+}
+
+impl std::ops::Deref for Errors {
+    type Target = [Error];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IntoIterator for Errors {
+    type Item = Error;
+    type IntoIter = std::vec::IntoIter<Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Errors {
+    type Item = &'a Error;
+    type IntoIter = std::slice::Iter<'a, Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl std::fmt::Display for Errors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, error) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Errors {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0
+            .first()
+            .map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
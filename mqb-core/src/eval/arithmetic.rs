@@ -0,0 +1,331 @@
+use bson::{Bson, Decimal128};
+
+use super::compare;
+
+pub(crate) fn add(lhs: &Bson, rhs: &Bson) -> Result<Bson, String> {
+    apply(
+        lhs,
+        rhs,
+        i32::checked_add,
+        i64::checked_add,
+        |lhs, rhs| lhs + rhs,
+        Decimal::add,
+    )
+}
+
+pub(crate) fn multiply(lhs: &Bson, rhs: &Bson) -> Result<Bson, String> {
+    apply(
+        lhs,
+        rhs,
+        i32::checked_mul,
+        i64::checked_mul,
+        |lhs, rhs| lhs * rhs,
+        Decimal::multiply,
+    )
+}
+
+// `$mul` on a missing field stores zero of the operand's type.
+pub(crate) fn zero(operand: &Bson) -> Result<Bson, String> {
+    match operand {
+        Bson::Int32(_) => Ok(Bson::Int32(0)),
+        Bson::Int64(_) => Ok(Bson::Int64(0)),
+        Bson::Double(_) => Ok(Bson::Double(0.0)),
+        Bson::Decimal128(_) => Decimal::integer(0).into_bson(),
+        operand => Err(format!(
+            "cannot apply arithmetic to {}",
+            compare::type_alias(operand)
+        )),
+    }
+}
+
+// Numeric results follow the server's type promotion: integers stay
+// integers while they fit and doubles win over integers.
+fn apply(
+    lhs: &Bson,
+    rhs: &Bson,
+    int32: fn(i32, i32) -> Option<i32>,
+    int64: fn(i64, i64) -> Option<i64>,
+    double: fn(f64, f64) -> f64,
+    decimal: fn(Decimal, Decimal) -> Decimal,
+) -> Result<Bson, String> {
+    match (lhs, rhs) {
+        (Bson::Int32(lhs), Bson::Int32(rhs)) => int32(*lhs, *rhs)
+            .map(Bson::Int32)
+            .or_else(|| int64(*lhs as i64, *rhs as i64).map(Bson::Int64))
+            .ok_or_else(|| "integer overflow".to_owned()),
+        (Bson::Int32(_) | Bson::Int64(_), Bson::Int32(_) | Bson::Int64(_)) => {
+            compare::as_i64(lhs)
+                .zip(compare::as_i64(rhs))
+                .and_then(|(lhs, rhs)| int64(lhs, rhs))
+                .map(Bson::Int64)
+                .ok_or_else(|| "integer overflow".to_owned())
+        }
+        (Bson::Decimal128(_), _) | (_, Bson::Decimal128(_)) => {
+            match (Decimal::parse(lhs), Decimal::parse(rhs)) {
+                (Some(lhs), Some(rhs)) => decimal(lhs, rhs).into_bson(),
+                _ => Err(format!(
+                    "cannot apply decimal arithmetic to {} and {}",
+                    lhs, rhs
+                )),
+            }
+        }
+        _ => match (compare::as_f64(lhs), compare::as_f64(rhs)) {
+            (Some(lhs), Some(rhs)) => Ok(Bson::Double(double(lhs, rhs))),
+            _ => Err(format!(
+                "cannot apply arithmetic to {} and {}",
+                compare::type_alias(lhs),
+                compare::type_alias(rhs)
+            )),
+        },
+    }
+}
+
+// Decimals are computed exactly on their digits, then rounded half to even
+// to the 34 digits and the exponent range of a Decimal128, as the server
+// does. Non-finite values aren't supported.
+struct Decimal {
+    negative: bool,
+    // Least significant digit first.
+    digits: Vec<u8>,
+    exponent: i32,
+}
+
+const DIGITS: usize = 34;
+const MIN_EXPONENT: i32 = -6176;
+const MAX_EXPONENT: i32 = 6111;
+
+impl Decimal {
+    fn parse(value: &Bson) -> Option<Self> {
+        let text = match value {
+            Bson::Int32(value) => return Some(Self::integer(*value as i128)),
+            Bson::Int64(value) => return Some(Self::integer(*value as i128)),
+            Bson::Double(value) if value.is_finite() => value.to_string(),
+            Bson::Decimal128(value) => value.to_string(),
+            _ => return None,
+        };
+
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(text) => (true, text),
+            None => (false, text.as_str()),
+        };
+
+        let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => {
+                (mantissa, exponent.parse::<i32>().ok()?)
+            }
+            None => (text, 0),
+        };
+
+        let (integer, fraction) =
+            mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        let digits = integer
+            .bytes()
+            .chain(fraction.bytes())
+            .rev()
+            .map(|digit| digit.is_ascii_digit().then(|| digit - b'0'))
+            .collect::<Option<Vec<_>>>()?;
+
+        if digits.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            negative,
+            digits,
+            exponent: exponent.checked_sub(fraction.len() as i32)?,
+        })
+    }
+
+    fn integer(value: i128) -> Self {
+        let digits = value
+            .unsigned_abs()
+            .to_string()
+            .bytes()
+            .rev()
+            .map(|digit| digit - b'0')
+            .collect();
+
+        Self {
+            negative: value < 0,
+            digits,
+            exponent: 0,
+        }
+    }
+
+    fn add(lhs: Self, rhs: Self) -> Self {
+        let exponent = lhs.exponent.min(rhs.exponent);
+        let lhs = lhs.scaled(exponent);
+        let rhs = rhs.scaled(exponent);
+
+        if lhs.negative == rhs.negative {
+            return Self {
+                negative: lhs.negative,
+                digits: add_digits(&lhs.digits, &rhs.digits),
+                exponent,
+            };
+        }
+
+        let (larger, smaller) =
+            if compare_digits(&lhs.digits, &rhs.digits).is_ge() {
+                (lhs, rhs)
+            } else {
+                (rhs, lhs)
+            };
+
+        Self {
+            negative: larger.negative,
+            digits: subtract_digits(&larger.digits, &smaller.digits),
+            exponent,
+        }
+    }
+
+    fn multiply(lhs: Self, rhs: Self) -> Self {
+        let mut digits = vec![0u32; lhs.digits.len() + rhs.digits.len()];
+
+        for (i, lhs) in lhs.digits.iter().enumerate() {
+            for (j, rhs) in rhs.digits.iter().enumerate() {
+                digits[i + j] += (*lhs as u32) * (*rhs as u32);
+            }
+        }
+
+        let mut carry = 0;
+        for digit in &mut digits {
+            *digit += carry;
+            carry = *digit / 10;
+            *digit %= 10;
+        }
+
+        Self {
+            negative: lhs.negative != rhs.negative,
+            digits: digits.into_iter().map(|digit| digit as u8).collect(),
+            exponent: lhs.exponent + rhs.exponent,
+        }
+    }
+
+    // The same value with a lower exponent and more digits.
+    fn scaled(mut self, exponent: i32) -> Self {
+        let shift = (self.exponent - exponent) as usize;
+        self.digits.splice(0..0, std::iter::repeat_n(0, shift));
+        self.exponent = exponent;
+        self
+    }
+
+    fn into_bson(mut self) -> Result<Bson, String> {
+        self.trim();
+
+        let excess = (self.digits.len().saturating_sub(DIGITS) as i64)
+            .max(MIN_EXPONENT as i64 - self.exponent as i64);
+        if excess > 0 {
+            self.round_off(excess as usize);
+        }
+
+        // Large exponents move into the coefficient while it has room.
+        if self.digits == [0] {
+            self.exponent = self.exponent.min(MAX_EXPONENT);
+        }
+        while self.exponent > MAX_EXPONENT && self.digits.len() < DIGITS {
+            self.digits.insert(0, 0);
+            self.exponent -= 1;
+        }
+        if self.exponent > MAX_EXPONENT {
+            return Err("decimal overflow".to_owned());
+        }
+
+        let digits = self
+            .digits
+            .iter()
+            .rev()
+            .map(|digit| char::from(b'0' + digit))
+            .collect::<String>();
+        let sign = if self.negative { "-" } else { "" };
+
+        format!("{}{}E{}", sign, digits, self.exponent)
+            .parse::<Decimal128>()
+            .map(Bson::Decimal128)
+            .map_err(|e| e.to_string())
+    }
+
+    fn trim(&mut self) {
+        while self.digits.len() > 1 && self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+    }
+
+    // Drops the `count` least significant digits, rounding half to even.
+    fn round_off(&mut self, count: usize) {
+        self.exponent += count as i32;
+
+        if count > self.digits.len() {
+            self.digits = vec![0];
+            return;
+        }
+
+        let dropped = self.digits.drain(..count).collect::<Vec<_>>();
+        let first = dropped[count - 1];
+        let rest = dropped[..count - 1].iter().any(|digit| *digit != 0);
+
+        if self.digits.is_empty() {
+            self.digits.push(0);
+        }
+
+        let odd = self.digits[0] % 2 == 1;
+        if first > 5 || first == 5 && (rest || odd) {
+            self.digits = add_digits(&self.digits, &[1]);
+
+            if self.digits.len() > DIGITS {
+                self.digits.remove(0);
+                self.exponent += 1;
+            }
+        }
+    }
+}
+
+fn add_digits(lhs: &[u8], rhs: &[u8]) -> Vec<u8> {
+    let mut digits = Vec::with_capacity(lhs.len().max(rhs.len()) + 1);
+    let mut carry = 0;
+
+    for index in 0..lhs.len().max(rhs.len()) {
+        let sum = lhs.get(index).copied().unwrap_or(0)
+            + rhs.get(index).copied().unwrap_or(0)
+            + carry;
+        digits.push(sum % 10);
+        carry = sum / 10;
+    }
+
+    if carry > 0 {
+        digits.push(carry);
+    }
+
+    digits
+}
+
+// `lhs` must not be smaller than `rhs`.
+fn subtract_digits(lhs: &[u8], rhs: &[u8]) -> Vec<u8> {
+    let mut digits = Vec::with_capacity(lhs.len());
+    let mut borrow = 0;
+
+    for (index, digit) in lhs.iter().enumerate() {
+        let subtrahend = rhs.get(index).copied().unwrap_or(0) + borrow;
+        if *digit >= subtrahend {
+            digits.push(digit - subtrahend);
+            borrow = 0;
+        } else {
+            digits.push(digit + 10 - subtrahend);
+            borrow = 1;
+        }
+    }
+
+    digits
+}
+
+fn compare_digits(lhs: &[u8], rhs: &[u8]) -> std::cmp::Ordering {
+    let significant = |digits: &[u8]| {
+        digits.len() - digits.iter().rev().take_while(|d| **d == 0).count()
+    };
+    let (lhs_len, rhs_len) = (significant(lhs), significant(rhs));
+
+    lhs_len.cmp(&rhs_len).then_with(|| {
+        lhs[..lhs_len].iter().rev().cmp(rhs[..rhs_len].iter().rev())
+    })
+}
@@ -0,0 +1,203 @@
+use std::cmp::Ordering;
+
+use bson::Bson;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Comparison {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl Comparison {
+    pub(crate) fn parse(operator: &str) -> Option<Self> {
+        match operator {
+            "$eq" => Some(Comparison::Eq),
+            "$ne" => Some(Comparison::Ne),
+            "$gt" => Some(Comparison::Gt),
+            "$gte" => Some(Comparison::Gte),
+            "$lt" => Some(Comparison::Lt),
+            "$lte" => Some(Comparison::Lte),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Eq => ordering.is_eq(),
+            Comparison::Ne => ordering.is_ne(),
+            Comparison::Gt => ordering.is_gt(),
+            Comparison::Gte => ordering.is_ge(),
+            Comparison::Lt => ordering.is_lt(),
+            Comparison::Lte => ordering.is_le(),
+        }
+    }
+}
+
+// Canonical type order used by MongoDB when comparing values of different
+// BSON types.
+pub(crate) fn type_rank(value: &Bson) -> u8 {
+    match value {
+        Bson::MinKey => 1,
+        Bson::Null | Bson::Undefined => 2,
+        Bson::Int32(_)
+        | Bson::Int64(_)
+        | Bson::Double(_)
+        | Bson::Decimal128(_) => 3,
+        Bson::String(_) | Bson::Symbol(_) => 4,
+        Bson::Document(_) => 5,
+        Bson::Array(_) => 6,
+        Bson::Binary(_) => 7,
+        Bson::ObjectId(_) => 8,
+        Bson::Boolean(_) => 9,
+        Bson::DateTime(_) => 10,
+        Bson::Timestamp(_) => 11,
+        Bson::RegularExpression(_) => 12,
+        Bson::DbPointer(_)
+        | Bson::JavaScriptCode(_)
+        | Bson::JavaScriptCodeWithScope(_) => 13,
+        Bson::MaxKey => 14,
+    }
+}
+
+pub(crate) fn compare(lhs: &Bson, rhs: &Bson) -> Ordering {
+    let rank = type_rank(lhs).cmp(&type_rank(rhs));
+    if rank != Ordering::Equal {
+        return rank;
+    }
+
+    match (lhs, rhs) {
+        (Bson::String(lhs) | Bson::Symbol(lhs), Bson::String(rhs))
+        | (Bson::String(lhs) | Bson::Symbol(lhs), Bson::Symbol(rhs)) => {
+            lhs.cmp(rhs)
+        }
+        (Bson::Document(lhs), Bson::Document(rhs)) => {
+            for ((lhs_key, lhs), (rhs_key, rhs)) in lhs.iter().zip(rhs) {
+                let ordering = type_rank(lhs)
+                    .cmp(&type_rank(rhs))
+                    .then_with(|| lhs_key.cmp(rhs_key))
+                    .then_with(|| compare(lhs, rhs));
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+
+            lhs.len().cmp(&rhs.len())
+        }
+        (Bson::Array(lhs), Bson::Array(rhs)) => {
+            for (lhs, rhs) in lhs.iter().zip(rhs) {
+                let ordering = compare(lhs, rhs);
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+
+            lhs.len().cmp(&rhs.len())
+        }
+        (Bson::Binary(lhs), Bson::Binary(rhs)) => lhs
+            .bytes
+            .len()
+            .cmp(&rhs.bytes.len())
+            .then_with(|| u8::from(lhs.subtype).cmp(&u8::from(rhs.subtype)))
+            .then_with(|| lhs.bytes.cmp(&rhs.bytes)),
+        (Bson::ObjectId(lhs), Bson::ObjectId(rhs)) => lhs.cmp(rhs),
+        (Bson::Boolean(lhs), Bson::Boolean(rhs)) => lhs.cmp(rhs),
+        (Bson::DateTime(lhs), Bson::DateTime(rhs)) => lhs.cmp(rhs),
+        (Bson::Timestamp(lhs), Bson::Timestamp(rhs)) => {
+            (lhs.time, lhs.increment).cmp(&(rhs.time, rhs.increment))
+        }
+        (Bson::RegularExpression(lhs), Bson::RegularExpression(rhs)) => lhs
+            .pattern
+            .cmp(&rhs.pattern)
+            .then_with(|| lhs.options.cmp(&rhs.options)),
+        (Bson::JavaScriptCode(lhs), Bson::JavaScriptCode(rhs)) => lhs.cmp(rhs),
+        _ => compare_numbers(lhs, rhs).unwrap_or(Ordering::Equal),
+    }
+}
+
+pub(crate) fn equals(lhs: &Bson, rhs: &Bson) -> bool {
+    compare(lhs, rhs) == Ordering::Equal
+}
+
+pub(crate) fn as_f64(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(value) => Some(*value as f64),
+        Bson::Int64(value) => Some(*value as f64),
+        Bson::Double(value) => Some(*value),
+        Bson::Decimal128(value) => value.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_i64(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int32(value) => Some(*value as i64),
+        Bson::Int64(value) => Some(*value),
+        _ => as_f64(value)
+            .filter(|value| value.is_finite())
+            .map(|value| value.trunc() as i64),
+    }
+}
+
+fn compare_numbers(lhs: &Bson, rhs: &Bson) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Bson::Int32(_) | Bson::Int64(_), Bson::Int32(_) | Bson::Int64(_)) => {
+            Some(as_i64(lhs)?.cmp(&as_i64(rhs)?))
+        }
+        _ => {
+            let (lhs, rhs) = (as_f64(lhs)?, as_f64(rhs)?);
+
+            // NaN sorts before every other number and equals itself.
+            Some(match (lhs.is_nan(), rhs.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => lhs.partial_cmp(&rhs)?,
+            })
+        }
+    }
+}
+
+pub(crate) fn type_alias(value: &Bson) -> &'static str {
+    match value {
+        Bson::Double(_) => "double",
+        Bson::String(_) => "string",
+        Bson::Document(_) => "object",
+        Bson::Array(_) => "array",
+        Bson::Binary(_) => "binData",
+        Bson::Undefined => "undefined",
+        Bson::ObjectId(_) => "objectId",
+        Bson::Boolean(_) => "bool",
+        Bson::DateTime(_) => "date",
+        Bson::Null => "null",
+        Bson::RegularExpression(_) => "regex",
+        Bson::DbPointer(_) => "dbPointer",
+        Bson::JavaScriptCode(_) => "javascript",
+        Bson::Symbol(_) => "symbol",
+        Bson::JavaScriptCodeWithScope(_) => "javascriptWithScope",
+        Bson::Int32(_) => "int",
+        Bson::Timestamp(_) => "timestamp",
+        Bson::Int64(_) => "long",
+        Bson::Decimal128(_) => "decimal",
+        Bson::MinKey => "minKey",
+        Bson::MaxKey => "maxKey",
+    }
+}
+
+pub(crate) fn has_type(value: &Bson, ty: &Bson) -> bool {
+    match ty {
+        Bson::String(alias) if alias == "number" => type_rank(value) == 3,
+        Bson::String(alias) => type_alias(value) == alias,
+        _ => as_i64(ty).is_some_and(|code| {
+            let element_type = value.element_type() as i64;
+            // MinKey and MaxKey are numbered -1 and 127 by the query
+            // language, but stored as 0xFF and 0x7F.
+            element_type == code || (code == -1 && element_type == 0xFF)
+        }),
+    }
+}
@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+
+use bson::{Bson, Document};
+
+use super::{
+    compare::{self, Comparison},
+    EvalError,
+};
+
+// The subset of aggregation expressions that `Expr` can produce.
+pub(crate) enum Expression {
+    Literal(Bson),
+    Field(Vec<String>),
+    Array(Vec<Expression>),
+    Object(Vec<(String, Expression)>),
+    Compare(Comparison, Box<Expression>, Box<Expression>),
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+    Not(Box<Expression>),
+}
+
+impl Expression {
+    pub(crate) fn compile(value: &Bson) -> Result<Self, EvalError> {
+        match value {
+            Bson::String(path) if path.starts_with("$$") => {
+                Err(EvalError::Unsupported(path.clone()))
+            }
+            Bson::String(path) if path.starts_with('$') => {
+                Ok(Expression::Field(
+                    path[1..].split('.').map(Into::into).collect(),
+                ))
+            }
+            Bson::Array(values) => Ok(Expression::Array(
+                values.iter().map(Self::compile).collect::<Result<_, _>>()?,
+            )),
+            Bson::Document(document) => Self::compile_document(document),
+            value => Ok(Expression::Literal(value.clone())),
+        }
+    }
+
+    fn compile_document(document: &Document) -> Result<Self, EvalError> {
+        let operator = match document.keys().next() {
+            Some(key) if key.starts_with('$') => key.as_str(),
+            _ => {
+                return Ok(Expression::Object(
+                    document
+                        .iter()
+                        .map(|(key, value)| {
+                            Ok((key.clone(), Self::compile(value)?))
+                        })
+                        .collect::<Result<_, EvalError>>()?,
+                ))
+            }
+        };
+
+        if document.len() != 1 {
+            return Err(EvalError::invalid(
+                operator,
+                "an expression must have a single operator",
+            ));
+        }
+
+        let operand = document.get(operator).unwrap();
+
+        if operator == "$literal" {
+            return Ok(Expression::Literal(operand.clone()));
+        }
+
+        let mut arguments = match operand {
+            Bson::Array(arguments) => arguments
+                .iter()
+                .map(Self::compile)
+                .collect::<Result<Vec<_>, _>>()?,
+            operand => vec![Self::compile(operand)?],
+        };
+
+        if let Some(comparison) = Comparison::parse(operator) {
+            if arguments.len() != 2 {
+                return Err(EvalError::invalid(
+                    operator,
+                    "expects exactly two arguments",
+                ));
+            }
+
+            let rhs = arguments.pop().unwrap();
+            let lhs = arguments.pop().unwrap();
+
+            return Ok(Expression::Compare(comparison, lhs.into(), rhs.into()));
+        }
+
+        match operator {
+            "$and" => Ok(Expression::And(arguments)),
+            "$or" => Ok(Expression::Or(arguments)),
+            "$not" if arguments.len() == 1 => {
+                Ok(Expression::Not(arguments.pop().unwrap().into()))
+            }
+            "$not" => Err(EvalError::invalid(
+                operator,
+                "expects exactly one argument",
+            )),
+            operator => Err(EvalError::Unsupported(operator.to_owned())),
+        }
+    }
+
+    // `None` stands for a missing field, which aggregation keeps distinct
+    // from null.
+    pub(crate) fn evaluate(&self, document: &Document) -> Option<Bson> {
+        match self {
+            Expression::Literal(value) => Some(value.clone()),
+            Expression::Field(path) => {
+                let (first, rest) = path.split_first()?;
+                field(document.get(first)?, rest)
+            }
+            Expression::Array(values) => Some(Bson::Array(
+                values
+                    .iter()
+                    .map(|value| value.evaluate(document).unwrap_or(Bson::Null))
+                    .collect(),
+            )),
+            Expression::Object(fields) => Some(Bson::Document(
+                fields
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        Some((key.clone(), value.evaluate(document)?))
+                    })
+                    .collect(),
+            )),
+            Expression::Compare(comparison, lhs, rhs) => {
+                let ordering =
+                    match (lhs.evaluate(document), rhs.evaluate(document)) {
+                        (Some(lhs), Some(rhs)) => compare::compare(&lhs, &rhs),
+                        (None, None) => Ordering::Equal,
+                        (None, Some(_)) => Ordering::Less,
+                        (Some(_), None) => Ordering::Greater,
+                    };
+
+                Some(Bson::Boolean(comparison.holds(ordering)))
+            }
+            Expression::And(arguments) => Some(Bson::Boolean(
+                arguments.iter().all(|argument| argument.is_true(document)),
+            )),
+            Expression::Or(arguments) => Some(Bson::Boolean(
+                arguments.iter().any(|argument| argument.is_true(document)),
+            )),
+            Expression::Not(argument) => {
+                Some(Bson::Boolean(!argument.is_true(document)))
+            }
+        }
+    }
+
+    pub(crate) fn is_true(&self, document: &Document) -> bool {
+        match self.evaluate(document) {
+            None
+            | Some(Bson::Null | Bson::Undefined | Bson::Boolean(false)) => {
+                false
+            }
+            Some(value) => compare::as_f64(&value).is_none_or(|n| n != 0.0),
+        }
+    }
+}
+
+fn field(value: &Bson, path: &[String]) -> Option<Bson> {
+    let Some((first, rest)) = path.split_first() else {
+        return Some(value.clone());
+    };
+
+    match value {
+        Bson::Document(document) => field(document.get(first)?, rest),
+        Bson::Array(elements) => Some(Bson::Array(
+            elements
+                .iter()
+                .filter_map(|element| field(element, path))
+                .collect(),
+        )),
+        _ => None,
+    }
+}
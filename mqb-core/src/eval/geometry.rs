@@ -0,0 +1,188 @@
+use bson::Bson;
+
+use crate::geo::{LineString, MultiPolygon, Point, Polygon, Position};
+
+use super::EvalError;
+
+const EARTH_RADIUS_METERS: f64 = 6_378_100.0;
+
+// Shapes are compared on a flat longitude/latitude plane, which is close
+// enough to the server's spherical geometry for small areas.
+pub(crate) struct Shape {
+    points: Vec<Position>,
+    segments: Vec<(Position, Position)>,
+    polygons: Vec<Vec<Vec<Position>>>,
+}
+
+impl Shape {
+    pub(crate) fn parse(value: &Bson) -> Option<Self> {
+        let Bson::Document(document) = value else {
+            return None;
+        };
+
+        let polygons = match document.get_str("type").ok()? {
+            "Point" => {
+                let point = bson::from_bson::<Point>(value.clone()).ok()?;
+                return Some(Self {
+                    points: vec![point.coordinates],
+                    segments: Vec::new(),
+                    polygons: Vec::new(),
+                });
+            }
+            "LineString" => {
+                let line = bson::from_bson::<LineString>(value.clone()).ok()?;
+                return Some(Self {
+                    segments: segments(&line.coordinates),
+                    points: line.coordinates,
+                    polygons: Vec::new(),
+                });
+            }
+            "Polygon" => {
+                vec![
+                    bson::from_bson::<Polygon>(value.clone()).ok()?.coordinates,
+                ]
+            }
+            "MultiPolygon" => {
+                bson::from_bson::<MultiPolygon>(value.clone())
+                    .ok()?
+                    .coordinates
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            points: polygons.iter().flatten().flatten().copied().collect(),
+            segments: polygons
+                .iter()
+                .flatten()
+                .flat_map(|ring| segments(ring))
+                .collect(),
+            polygons,
+        })
+    }
+
+    pub(crate) fn compile(
+        value: &Bson,
+        operator: &str,
+    ) -> Result<Self, EvalError> {
+        let geometry = match value {
+            Bson::Document(document) => document.get("$geometry"),
+            _ => None,
+        };
+
+        geometry.and_then(Self::parse).ok_or_else(|| {
+            EvalError::invalid(operator, "expects a GeoJSON $geometry")
+        })
+    }
+
+    pub(crate) fn point(&self) -> Option<Position> {
+        match (self.points.as_slice(), self.segments.is_empty()) {
+            ([point], true) => Some(*point),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn within(&self, area: &Shape) -> bool {
+        !self.points.is_empty()
+            && self.points.iter().all(|point| area.covers(*point))
+    }
+
+    pub(crate) fn intersects(&self, other: &Shape) -> bool {
+        self.points.iter().any(|point| other.touches(*point))
+            || other.points.iter().any(|point| self.touches(*point))
+            || self.segments.iter().any(|lhs| {
+                other
+                    .segments
+                    .iter()
+                    .any(|rhs| segments_intersect(*lhs, *rhs))
+            })
+    }
+
+    fn covers(&self, point: Position) -> bool {
+        self.polygons.iter().any(|rings| {
+            let Some((exterior, holes)) = rings.split_first() else {
+                return false;
+            };
+
+            (on_ring(exterior, point) || in_ring(exterior, point))
+                && !holes.iter().any(|hole| in_ring(hole, point))
+        })
+    }
+
+    fn touches(&self, point: Position) -> bool {
+        self.points.contains(&point)
+            || self
+                .segments
+                .iter()
+                .any(|segment| on_segment(*segment, point))
+            || self.covers(point)
+    }
+}
+
+pub(crate) fn distance_meters(from: Position, to: Position) -> f64 {
+    let (from_lat, to_lat) = (from[1].to_radians(), to[1].to_radians());
+    let delta_lat = to_lat - from_lat;
+    let delta_lng = (to[0] - from[0]).to_radians();
+
+    let a = (delta_lat / 2.0).sin().powi(2)
+        + from_lat.cos() * to_lat.cos() * (delta_lng / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+fn segments(points: &[Position]) -> Vec<(Position, Position)> {
+    points.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+fn in_ring(ring: &[Position], point: Position) -> bool {
+    let mut inside = false;
+
+    for (start, end) in segments(ring) {
+        if (start[1] > point[1]) != (end[1] > point[1]) {
+            let crossing = start[0]
+                + (point[1] - start[1]) / (end[1] - start[1])
+                    * (end[0] - start[0]);
+
+            if point[0] < crossing {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+fn on_ring(ring: &[Position], point: Position) -> bool {
+    segments(ring)
+        .into_iter()
+        .any(|segment| on_segment(segment, point))
+}
+
+fn orientation(a: Position, b: Position, c: Position) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn on_segment((start, end): (Position, Position), point: Position) -> bool {
+    orientation(start, end, point).abs() < f64::EPSILON
+        && point[0] >= start[0].min(end[0])
+        && point[0] <= start[0].max(end[0])
+        && point[1] >= start[1].min(end[1])
+        && point[1] <= start[1].max(end[1])
+}
+
+fn segments_intersect(
+    lhs: (Position, Position),
+    rhs: (Position, Position),
+) -> bool {
+    let d1 = orientation(rhs.0, rhs.1, lhs.0);
+    let d2 = orientation(rhs.0, rhs.1, lhs.1);
+    let d3 = orientation(lhs.0, lhs.1, rhs.0);
+    let d4 = orientation(lhs.0, lhs.1, rhs.1);
+
+    ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+        || on_segment(rhs, lhs.0)
+        || on_segment(rhs, lhs.1)
+        || on_segment(lhs, rhs.0)
+        || on_segment(lhs, rhs.1)
+}
@@ -0,0 +1,709 @@
+use std::marker::PhantomData;
+
+use bson::{Bson, Document};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{geo::Position, FilterBuilder};
+
+use super::{
+    compare::{self, Comparison},
+    expression::Expression,
+    geometry::{self, Shape},
+    path,
+    schema::SchemaValidator,
+    text::TextSearch,
+    EvalError,
+};
+
+pub struct Matcher<T> {
+    document: Document,
+    predicate: Predicate,
+    near: Option<(String, Position)>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Matcher<T> {
+    pub fn new(filter: FilterBuilder<T>) -> Result<Self, EvalError> {
+        let document = filter.try_build().map_err(EvalError::Build)?;
+        let predicate = Predicate::compile(&document)?;
+        let near = near_center(&document);
+
+        Ok(Self {
+            document,
+            predicate,
+            near,
+            marker: PhantomData,
+        })
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    pub fn matches_document(&self, document: &Document) -> bool {
+        self.predicate.matches(document)
+    }
+
+    // `$near` and `$nearSphere` return documents nearest first, other
+    // queries keep the order the documents are given in.
+    pub fn sort_nearest_first(&self, documents: &mut [&Document]) {
+        let Some((path, center)) = &self.near else {
+            return;
+        };
+
+        let distance = |document: &Document| {
+            path::lookup(document, path)
+                .into_iter()
+                .flatten()
+                .filter_map(|value| Shape::parse(value)?.point())
+                .map(|point| geometry::distance_meters(*center, point))
+                .min_by(f64::total_cmp)
+                .unwrap_or(f64::INFINITY)
+        };
+
+        documents.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+    }
+
+    // Upserts start from the equality conditions of the filter, the same
+    // way the server seeds a new document.
+    pub fn upsert_document(&self) -> Document {
+        let mut document = Document::new();
+        collect_equalities(&self.document, &mut document);
+        document
+    }
+
+    // The positional operator stands for the first element of the array
+    // which the query needs to match: the document matches with only that
+    // element, but not with the array emptied.
+    pub(crate) fn first_match(
+        &self,
+        document: &Document,
+        array_path: &str,
+    ) -> Option<usize> {
+        let Some(Bson::Array(elements)) = path::get(document, array_path)
+        else {
+            return None;
+        };
+
+        let matches_with = |elements: Vec<Bson>| {
+            let mut candidate = document.clone();
+            path::set(&mut candidate, array_path, Bson::Array(elements)).is_ok()
+                && self.predicate.matches(&candidate)
+        };
+
+        if matches_with(Vec::new()) {
+            return None;
+        }
+
+        elements
+            .iter()
+            .position(|element| matches_with(vec![element.clone()]))
+    }
+}
+
+// A query holds at most one `$near` or `$nearSphere`, on a top level key
+// path.
+fn near_center(filter: &Document) -> Option<(String, Position)> {
+    filter.iter().find_map(|(key, value)| {
+        let Bson::Document(operators) = value else {
+            return None;
+        };
+        let (operator, operand) = operators
+            .iter()
+            .find(|(op, _)| matches!(op.as_str(), "$near" | "$nearSphere"))?;
+        let center = Shape::compile(operand, operator).ok()?.point()?;
+
+        Some((key.clone(), center))
+    })
+}
+
+fn collect_equalities(filter: &Document, document: &mut Document) {
+    for (key, value) in filter {
+        if key == "$and" {
+            if let Bson::Array(clauses) = value {
+                for clause in clauses {
+                    if let Bson::Document(clause) = clause {
+                        collect_equalities(clause, document);
+                    }
+                }
+            }
+            continue;
+        }
+
+        if key.starts_with('$') {
+            continue;
+        }
+
+        let value = match value {
+            Bson::Document(operators)
+                if operators.keys().any(|op| op.starts_with('$')) =>
+            {
+                match operators.get("$eq") {
+                    Some(value) => value,
+                    None => continue,
+                }
+            }
+            value => value,
+        };
+
+        // Conflicting paths are left for the update to report.
+        let _ = path::set(document, key, value.clone());
+    }
+}
+
+impl<T: Serialize> Matcher<T> {
+    // Values which do not serialize to a document never match.
+    pub fn matches(&self, value: &T) -> bool {
+        bson::to_document(value)
+            .is_ok_and(|document| self.matches_document(&document))
+    }
+}
+
+impl<T> TryFrom<FilterBuilder<T>> for Matcher<T> {
+    type Error = EvalError;
+
+    fn try_from(filter: FilterBuilder<T>) -> Result<Self, Self::Error> {
+        Self::new(filter)
+    }
+}
+
+pub(crate) enum Predicate {
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Nor(Vec<Predicate>),
+    Field(String, Vec<Condition>),
+    Expr(Expression),
+    Text(TextSearch),
+    JsonSchema(SchemaValidator),
+}
+
+impl Predicate {
+    pub(crate) fn compile(document: &Document) -> Result<Self, EvalError> {
+        let mut predicates = Vec::new();
+
+        for (key, value) in document {
+            predicates.push(match key.as_str() {
+                "$and" => Predicate::And(Self::compile_clauses(key, value)?),
+                "$or" => Predicate::Or(Self::compile_clauses(key, value)?),
+                "$nor" => Predicate::Nor(Self::compile_clauses(key, value)?),
+                "$expr" => Predicate::Expr(Expression::compile(value)?),
+                "$text" => Predicate::Text(TextSearch::compile(value)?),
+                "$jsonSchema" => {
+                    Predicate::JsonSchema(SchemaValidator::compile(value)?)
+                }
+                "$comment" => continue,
+                operator if operator.starts_with('$') => {
+                    return Err(EvalError::Unsupported(operator.to_owned()))
+                }
+                path => Predicate::Field(
+                    path.to_owned(),
+                    Condition::compile(value)?,
+                ),
+            });
+        }
+
+        Ok(Predicate::And(predicates))
+    }
+
+    fn compile_clauses(
+        operator: &str,
+        value: &Bson,
+    ) -> Result<Vec<Predicate>, EvalError> {
+        match value {
+            Bson::Array(clauses) if !clauses.is_empty() => clauses
+                .iter()
+                .map(|clause| match clause {
+                    Bson::Document(clause) => Self::compile(clause),
+                    _ => Err(EvalError::invalid(operator, "expects documents")),
+                })
+                .collect(),
+            _ => Err(EvalError::invalid(operator, "expects a non-empty array")),
+        }
+    }
+
+    pub(crate) fn matches(&self, document: &Document) -> bool {
+        match self {
+            Predicate::And(predicates) => predicates
+                .iter()
+                .all(|predicate| predicate.matches(document)),
+            Predicate::Or(predicates) => predicates
+                .iter()
+                .any(|predicate| predicate.matches(document)),
+            Predicate::Nor(predicates) => !predicates
+                .iter()
+                .any(|predicate| predicate.matches(document)),
+            Predicate::Field(path, conditions) => {
+                let values = path::lookup(document, path);
+                conditions
+                    .iter()
+                    .all(|condition| condition.matches(&values))
+            }
+            Predicate::Expr(expression) => expression.is_true(document),
+            Predicate::Text(search) => search.matches(document),
+            Predicate::JsonSchema(validator) => validator.matches(document),
+        }
+    }
+}
+
+pub(crate) enum Condition {
+    Compare(Comparison, Bson),
+    In(Vec<Pattern>),
+    Nin(Vec<Pattern>),
+    Exists(bool),
+    Type(Vec<Bson>),
+    Mod(i64, i64),
+    Regex(Regex),
+    Size(usize),
+    All(Vec<Bson>),
+    ElemMatch(ElemMatch),
+    Not(Vec<Condition>),
+    Bits(Bits, Vec<u32>),
+    GeoWithin(Shape),
+    GeoIntersects(Shape),
+    Near {
+        center: Position,
+        min_distance: Option<f64>,
+        max_distance: Option<f64>,
+    },
+}
+
+pub(crate) enum Pattern {
+    Value(Bson),
+    Regex(Regex),
+}
+
+pub(crate) enum ElemMatch {
+    Value(Vec<Condition>),
+    Document(Predicate),
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Bits {
+    AllSet,
+    AnySet,
+    AllClear,
+    AnyClear,
+}
+
+impl Condition {
+    pub(crate) fn compile(value: &Bson) -> Result<Vec<Self>, EvalError> {
+        match value {
+            Bson::Document(operators) if is_operator_document(operators) => {
+                operators
+                    .iter()
+                    .filter(|(operator, _)| *operator != "$options")
+                    .map(|(operator, operand)| {
+                        Self::compile_operator(operator, operand, operators)
+                    })
+                    .collect()
+            }
+            Bson::RegularExpression(regex) => Ok(vec![Condition::Regex(
+                compile_regex(&regex.pattern, &regex.options)?,
+            )]),
+            value => {
+                Ok(vec![Condition::Compare(Comparison::Eq, value.clone())])
+            }
+        }
+    }
+
+    fn compile_operator(
+        operator: &str,
+        operand: &Bson,
+        operators: &Document,
+    ) -> Result<Self, EvalError> {
+        if let Some(comparison) = Comparison::parse(operator) {
+            return Ok(Condition::Compare(comparison, operand.clone()));
+        }
+
+        Ok(match operator {
+            "$in" => Condition::In(compile_patterns(operator, operand)?),
+            "$nin" => Condition::Nin(compile_patterns(operator, operand)?),
+            "$exists" => Condition::Exists(match operand {
+                Bson::Boolean(exists) => *exists,
+                operand => compare::as_f64(operand).is_some_and(|n| n != 0.0),
+            }),
+            "$type" => Condition::Type(match operand {
+                Bson::Array(types) => types.clone(),
+                ty => vec![ty.clone()],
+            }),
+            "$mod" => match operand {
+                Bson::Array(operands) if operands.len() == 2 => {
+                    match (
+                        compare::as_i64(&operands[0]),
+                        compare::as_i64(&operands[1]),
+                    ) {
+                        (Some(divisor), Some(remainder)) if divisor != 0 => {
+                            Condition::Mod(divisor, remainder)
+                        }
+                        _ => {
+                            return Err(EvalError::invalid(
+                                operator,
+                                "expects a non-zero divisor and a remainder",
+                            ))
+                        }
+                    }
+                }
+                _ => {
+                    return Err(EvalError::invalid(
+                        operator,
+                        "expects [divisor, remainder]",
+                    ))
+                }
+            },
+            "$regex" => {
+                let options = operators.get_str("$options").unwrap_or("");
+
+                match operand {
+                    Bson::String(pattern) => {
+                        Condition::Regex(compile_regex(pattern, options)?)
+                    }
+                    Bson::RegularExpression(regex) => {
+                        Condition::Regex(compile_regex(
+                            &regex.pattern,
+                            &format!("{}{}", regex.options, options),
+                        )?)
+                    }
+                    _ => {
+                        return Err(EvalError::invalid(
+                            operator,
+                            "expects a pattern",
+                        ))
+                    }
+                }
+            }
+            "$size" => match compare::as_i64(operand) {
+                Some(size) if size >= 0 => Condition::Size(size as usize),
+                _ => {
+                    return Err(EvalError::invalid(
+                        operator,
+                        "expects a non-negative integer",
+                    ))
+                }
+            },
+            "$all" => match operand {
+                Bson::Array(values) => Condition::All(values.clone()),
+                _ => {
+                    return Err(EvalError::invalid(
+                        operator,
+                        "expects an array",
+                    ))
+                }
+            },
+            "$elemMatch" => match operand {
+                Bson::Document(filter) if is_value_filter(filter) => {
+                    Condition::ElemMatch(ElemMatch::Value(Self::compile(
+                        operand,
+                    )?))
+                }
+                Bson::Document(filter) => Condition::ElemMatch(
+                    ElemMatch::Document(Predicate::compile(filter)?),
+                ),
+                _ => {
+                    return Err(EvalError::invalid(
+                        operator,
+                        "expects a document",
+                    ))
+                }
+            },
+            "$not" => match operand {
+                Bson::Document(negated) if is_operator_document(negated) => {
+                    Condition::Not(Self::compile(operand)?)
+                }
+                Bson::RegularExpression(_) => {
+                    Condition::Not(Self::compile(operand)?)
+                }
+                _ => {
+                    return Err(EvalError::invalid(
+                        operator,
+                        "expects an operator document or a regex",
+                    ))
+                }
+            },
+            "$bitsAllSet" => {
+                Condition::Bits(Bits::AllSet, bit_positions(operand)?)
+            }
+            "$bitsAnySet" => {
+                Condition::Bits(Bits::AnySet, bit_positions(operand)?)
+            }
+            "$bitsAllClear" => {
+                Condition::Bits(Bits::AllClear, bit_positions(operand)?)
+            }
+            "$bitsAnyClear" => {
+                Condition::Bits(Bits::AnyClear, bit_positions(operand)?)
+            }
+            "$geoWithin" => {
+                Condition::GeoWithin(Shape::compile(operand, operator)?)
+            }
+            "$geoIntersects" => {
+                Condition::GeoIntersects(Shape::compile(operand, operator)?)
+            }
+            "$near" | "$nearSphere" => {
+                let center =
+                    Shape::compile(operand, operator)?.point().ok_or_else(
+                        || EvalError::invalid(operator, "expects a Point"),
+                    )?;
+                let distance = |key| match operand {
+                    Bson::Document(operand) => {
+                        operand.get(key).and_then(compare::as_f64)
+                    }
+                    _ => None,
+                };
+
+                Condition::Near {
+                    center,
+                    min_distance: distance("$minDistance"),
+                    max_distance: distance("$maxDistance"),
+                }
+            }
+            operator => {
+                return Err(EvalError::Unsupported(operator.to_owned()))
+            }
+        })
+    }
+
+    pub(crate) fn matches(&self, values: &[Option<&Bson>]) -> bool {
+        match self {
+            Condition::Compare(Comparison::Eq, value) => equals(values, value),
+            Condition::Compare(Comparison::Ne, value) => !equals(values, value),
+            // Null matches missing fields, so the inclusive bounds on it
+            // behave like equality.
+            Condition::Compare(
+                Comparison::Gte | Comparison::Lte,
+                value @ Bson::Null,
+            ) => equals(values, value),
+            Condition::Compare(comparison, value) => {
+                any_element(values, |candidate| {
+                    compare::type_rank(candidate) == compare::type_rank(value)
+                        && comparison.holds(compare::compare(candidate, value))
+                })
+            }
+            Condition::In(patterns) => {
+                patterns.iter().any(|pattern| pattern.matches(values))
+            }
+            Condition::Nin(patterns) => {
+                !patterns.iter().any(|pattern| pattern.matches(values))
+            }
+            Condition::Exists(exists) => {
+                values.iter().any(Option::is_some) == *exists
+            }
+            Condition::Type(types) => any_element(values, |candidate| {
+                types.iter().any(|ty| compare::has_type(candidate, ty))
+            }),
+            Condition::Mod(divisor, remainder) => {
+                any_element(values, |candidate| {
+                    compare::type_rank(candidate) == 3
+                        && compare::as_i64(candidate)
+                            .is_some_and(|n| n % divisor == *remainder)
+                })
+            }
+            Condition::Regex(regex) => {
+                any_element(values, |candidate| is_match(regex, candidate))
+            }
+            Condition::Size(size) => values.iter().flatten().any(|value| {
+                matches!(value, Bson::Array(elements) if elements.len() == *size)
+            }),
+            Condition::All(required) => {
+                !required.is_empty()
+                    && required.iter().all(|value| equals(values, value))
+            }
+            Condition::ElemMatch(elem_match) => {
+                values.iter().flatten().any(|value| match value {
+                    Bson::Array(elements) => elements
+                        .iter()
+                        .any(|element| elem_match.matches(element)),
+                    _ => false,
+                })
+            }
+            Condition::Not(conditions) => {
+                !conditions.iter().all(|condition| condition.matches(values))
+            }
+            Condition::Bits(bits, positions) => {
+                any_element(values, |candidate| {
+                    bits_match(*bits, positions, candidate)
+                })
+            }
+            Condition::GeoWithin(area) => {
+                values.iter().flatten().any(|value| {
+                    Shape::parse(value).is_some_and(|shape| shape.within(area))
+                })
+            }
+            Condition::GeoIntersects(geometry) => {
+                values.iter().flatten().any(|value| {
+                    Shape::parse(value)
+                        .is_some_and(|shape| shape.intersects(geometry))
+                })
+            }
+            Condition::Near {
+                center,
+                min_distance,
+                max_distance,
+            } => values.iter().flatten().any(|value| {
+                let Some(point) = Shape::parse(value).and_then(|s| s.point())
+                else {
+                    return false;
+                };
+                let distance = geometry::distance_meters(*center, point);
+
+                min_distance.is_none_or(|min| distance >= min)
+                    && max_distance.is_none_or(|max| distance <= max)
+            }),
+        }
+    }
+}
+
+impl Pattern {
+    fn matches(&self, values: &[Option<&Bson>]) -> bool {
+        match self {
+            Pattern::Value(value) => equals(values, value),
+            Pattern::Regex(regex) => {
+                any_element(values, |candidate| is_match(regex, candidate))
+            }
+        }
+    }
+}
+
+impl ElemMatch {
+    fn matches(&self, element: &Bson) -> bool {
+        match (self, element) {
+            (ElemMatch::Value(conditions), element) => conditions
+                .iter()
+                .all(|condition| condition.matches(&[Some(element)])),
+            (ElemMatch::Document(predicate), Bson::Document(element)) => {
+                predicate.matches(element)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn is_operator_document(document: &Document) -> bool {
+    document
+        .keys()
+        .next()
+        .is_some_and(|key| key.starts_with('$'))
+}
+
+// `$elemMatch` over scalars applies operators directly to each element,
+// while a document filter names fields of the elements.
+pub(crate) fn is_value_filter(filter: &Document) -> bool {
+    is_operator_document(filter)
+        && !filter.keys().any(|key| {
+            matches!(key.as_str(), "$and" | "$or" | "$nor" | "$expr")
+        })
+}
+
+// Conditions hold when any candidate value, or any element of a candidate
+// array, satisfies them.
+fn any_element(
+    values: &[Option<&Bson>],
+    predicate: impl Fn(&Bson) -> bool,
+) -> bool {
+    values.iter().flatten().any(|value| {
+        predicate(value)
+            || matches!(value, Bson::Array(elements) if elements.iter().any(&predicate))
+    })
+}
+
+fn equals(values: &[Option<&Bson>], value: &Bson) -> bool {
+    (matches!(value, Bson::Null) && values.iter().any(Option::is_none))
+        || any_element(values, |candidate| compare::equals(candidate, value))
+}
+
+fn is_match(regex: &Regex, value: &Bson) -> bool {
+    matches!(value, Bson::String(value) | Bson::Symbol(value) if regex.is_match(value))
+}
+
+fn compile_regex(pattern: &str, options: &str) -> Result<Regex, EvalError> {
+    let mut builder = regex::RegexBuilder::new(pattern);
+
+    for option in options.chars() {
+        match option {
+            'i' => builder.case_insensitive(true),
+            'm' => builder.multi_line(true),
+            'x' => builder.ignore_whitespace(true),
+            's' => builder.dot_matches_new_line(true),
+            option => {
+                return Err(EvalError::invalid(
+                    "$options",
+                    format!("unknown option {}", option),
+                ))
+            }
+        };
+    }
+
+    builder
+        .build()
+        .map_err(|e| EvalError::invalid("$regex", e.to_string()))
+}
+
+fn compile_patterns(
+    operator: &str,
+    operand: &Bson,
+) -> Result<Vec<Pattern>, EvalError> {
+    let Bson::Array(values) = operand else {
+        return Err(EvalError::invalid(operator, "expects an array"));
+    };
+
+    values
+        .iter()
+        .map(|value| match value {
+            Bson::RegularExpression(regex) => Ok(Pattern::Regex(
+                compile_regex(&regex.pattern, &regex.options)?,
+            )),
+            value => Ok(Pattern::Value(value.clone())),
+        })
+        .collect()
+}
+
+fn bit_positions(operand: &Bson) -> Result<Vec<u32>, EvalError> {
+    match operand {
+        Bson::Array(positions) => positions
+            .iter()
+            .map(|position| {
+                compare::as_i64(position)
+                    .and_then(|position| u32::try_from(position).ok())
+                    .ok_or_else(|| {
+                        EvalError::invalid(
+                            "$bits",
+                            "positions must be non-negative integers",
+                        )
+                    })
+            })
+            .collect(),
+        operand => {
+            let mask = compare::as_i64(operand).ok_or_else(|| {
+                EvalError::invalid("$bits", "expects a mask or positions")
+            })?;
+
+            Ok((0..64)
+                .filter(|position| mask >> position & 1 == 1)
+                .collect())
+        }
+    }
+}
+
+fn bits_match(bits: Bits, positions: &[u32], value: &Bson) -> bool {
+    let number = match value {
+        Bson::Int32(_) | Bson::Int64(_) => compare::as_i64(value),
+        Bson::Double(n) if n.fract() == 0.0 => compare::as_i64(value),
+        _ => None,
+    };
+
+    let Some(number) = number else {
+        return false;
+    };
+
+    // Bits beyond the width of the value repeat its sign.
+    let is_set = |position: &u32| match position {
+        0..=63 => number >> position & 1 == 1,
+        _ => number < 0,
+    };
+
+    match bits {
+        Bits::AllSet => positions.iter().all(is_set),
+        Bits::AnySet => positions.iter().any(is_set),
+        Bits::AllClear => !positions.iter().any(is_set),
+        Bits::AnyClear => !positions.iter().all(is_set),
+    }
+}
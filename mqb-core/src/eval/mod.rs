@@ -0,0 +1,61 @@
+mod arithmetic;
+mod compare;
+mod expression;
+mod geometry;
+mod matcher;
+mod path;
+mod schema;
+mod text;
+mod updater;
+
+pub use matcher::*;
+pub use updater::*;
+
+#[derive(Debug)]
+pub enum EvalError {
+    Build(crate::Errors),
+    Serialization(bson::ser::Error),
+    Deserialization(bson::de::Error),
+    Unsupported(String),
+    Invalid { operator: String, message: String },
+}
+
+impl EvalError {
+    pub(crate) fn invalid(operator: &str, message: impl Into<String>) -> Self {
+        EvalError::Invalid {
+            operator: operator.to_owned(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::Build(e) => write!(f, "failed to build: {}", e),
+            EvalError::Serialization(e) => {
+                write!(f, "failed to serialize: {}", e)
+            }
+            EvalError::Deserialization(e) => {
+                write!(f, "failed to deserialize: {}", e)
+            }
+            EvalError::Unsupported(operator) => {
+                write!(f, "{} cannot be evaluated in memory", operator)
+            }
+            EvalError::Invalid { operator, message } => {
+                write!(f, "{}: {}", operator, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EvalError::Build(e) => Some(e),
+            EvalError::Serialization(e) => Some(e),
+            EvalError::Deserialization(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use bson::{Bson, Document};
+
+// Resolves a dotted path the way the query language does: arrays met along
+// the way are traversed element by element, and `None` marks every branch
+// on which the path does not exist.
+pub(crate) fn lookup<'a>(
+    document: &'a Document,
+    path: &str,
+) -> Vec<Option<&'a Bson>> {
+    let segments = path.split('.').collect::<Vec<_>>();
+    let mut values = Vec::new();
+
+    match document.get(segments[0]) {
+        Some(value) => lookup_value(value, &segments[1..], &mut values),
+        None => values.push(None),
+    }
+
+    values
+}
+
+fn lookup_value<'a>(
+    value: &'a Bson,
+    segments: &[&str],
+    values: &mut Vec<Option<&'a Bson>>,
+) {
+    let Some((segment, rest)) = segments.split_first() else {
+        values.push(Some(value));
+        return;
+    };
+
+    match value {
+        Bson::Document(document) => match document.get(*segment) {
+            Some(value) => lookup_value(value, rest, values),
+            None => values.push(None),
+        },
+        Bson::Array(elements) => {
+            if let Ok(index) = segment.parse::<usize>() {
+                match elements.get(index) {
+                    Some(value) => lookup_value(value, rest, values),
+                    None => values.push(None),
+                }
+                return;
+            }
+
+            let mut traversed = false;
+
+            for element in elements {
+                if let Bson::Document(_) = element {
+                    lookup_value(element, segments, values);
+                    traversed = true;
+                }
+            }
+
+            if !traversed {
+                values.push(None);
+            }
+        }
+        _ => values.push(None),
+    }
+}
+
+pub(crate) fn get<'a>(document: &'a Document, path: &str) -> Option<&'a Bson> {
+    let mut segments = path.split('.');
+    let mut value = document.get(segments.next()?)?;
+
+    for segment in segments {
+        value = match value {
+            Bson::Document(document) => document.get(segment)?,
+            Bson::Array(elements) => {
+                elements.get(segment.parse::<usize>().ok()?)?
+            }
+            _ => return None,
+        };
+    }
+
+    Some(value)
+}
+
+pub(crate) fn get_mut<'a>(
+    document: &'a mut Document,
+    path: &str,
+) -> Option<&'a mut Bson> {
+    let mut segments = path.split('.');
+    let mut value = document.get_mut(segments.next()?)?;
+
+    for segment in segments {
+        value = match value {
+            Bson::Document(document) => document.get_mut(segment)?,
+            Bson::Array(elements) => {
+                elements.get_mut(segment.parse::<usize>().ok()?)?
+            }
+            _ => return None,
+        };
+    }
+
+    Some(value)
+}
+
+// Writes `value` at `path`, creating intermediate documents the way the
+// update operators do.
+pub(crate) fn set(
+    document: &mut Document,
+    path: &str,
+    value: Bson,
+) -> Result<(), String> {
+    let segments = path.split('.').collect::<Vec<_>>();
+    let mut root = Bson::Document(std::mem::take(document));
+
+    let result = set_value(&mut root, &segments, value);
+
+    if let Bson::Document(root) = root {
+        *document = root;
+    }
+
+    result
+}
+
+fn set_value(
+    node: &mut Bson,
+    segments: &[&str],
+    value: Bson,
+) -> Result<(), String> {
+    let Some((segment, rest)) = segments.split_first() else {
+        *node = value;
+        return Ok(());
+    };
+
+    match node {
+        Bson::Document(document) => {
+            let child = document
+                .entry(segment.to_string())
+                .or_insert_with(|| Bson::Document(Document::new()));
+
+            if rest.is_empty() {
+                *child = value;
+                return Ok(());
+            }
+
+            set_value(child, rest, value)
+        }
+        Bson::Array(elements) => {
+            let index = segment.parse::<usize>().map_err(|_| {
+                format!("cannot create field '{}' in an array", segment)
+            })?;
+
+            if index >= elements.len() {
+                let padding = if rest.is_empty() {
+                    Bson::Null
+                } else {
+                    Bson::Document(Document::new())
+                };
+                elements.resize(index + 1, Bson::Null);
+                elements[index] = padding;
+            }
+
+            set_value(&mut elements[index], rest, value)
+        }
+        node => Err(format!(
+            "cannot create field '{}' in element {}",
+            segment, node
+        )),
+    }
+}
+
+// Removes the value at `path`. An array element is replaced by null rather
+// than shifting the elements after it, as `$unset` does.
+pub(crate) fn remove(document: &mut Document, path: &str) {
+    let Some((parent, last)) = path.rsplit_once('.') else {
+        document.remove(path);
+        return;
+    };
+
+    match get_mut(document, parent) {
+        Some(Bson::Document(document)) => {
+            document.remove(last);
+        }
+        Some(Bson::Array(elements)) => {
+            if let Some(element) = last
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| elements.get_mut(index))
+            {
+                *element = Bson::Null;
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,127 @@
+use bson::{Bson, Document};
+
+use super::{compare, EvalError};
+
+const KEYWORDS: &[&str] = &[
+    "bsonType",
+    "required",
+    "properties",
+    "additionalProperties",
+    "items",
+    "enum",
+    "title",
+    "description",
+];
+
+// Validates the keywords `JsonSchema` implementations produce.
+pub(crate) struct SchemaValidator {
+    schema: Document,
+}
+
+impl SchemaValidator {
+    pub(crate) fn compile(value: &Bson) -> Result<Self, EvalError> {
+        let Bson::Document(schema) = value else {
+            return Err(EvalError::invalid(
+                "$jsonSchema",
+                "expects a document",
+            ));
+        };
+
+        check_keywords(schema)?;
+
+        Ok(Self {
+            schema: schema.clone(),
+        })
+    }
+
+    pub(crate) fn matches(&self, document: &Document) -> bool {
+        validate(&self.schema, &Bson::Document(document.clone()))
+    }
+}
+
+fn check_keywords(schema: &Document) -> Result<(), EvalError> {
+    for (keyword, value) in schema {
+        if !KEYWORDS.contains(&keyword.as_str()) {
+            return Err(EvalError::Unsupported(format!(
+                "$jsonSchema keyword {}",
+                keyword
+            )));
+        }
+
+        match (keyword.as_str(), value) {
+            ("properties", Bson::Document(properties)) => {
+                for (_, property) in properties {
+                    if let Bson::Document(property) = property {
+                        check_keywords(property)?;
+                    }
+                }
+            }
+            ("additionalProperties" | "items", Bson::Document(schema)) => {
+                check_keywords(schema)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn validate(schema: &Document, value: &Bson) -> bool {
+    if let Some(bson_type) = schema.get("bsonType") {
+        let matches_type = match bson_type {
+            Bson::Array(types) => {
+                types.iter().any(|ty| compare::has_type(value, ty))
+            }
+            ty => compare::has_type(value, ty),
+        };
+
+        if !matches_type {
+            return false;
+        }
+    }
+
+    if let Ok(options) = schema.get_array("enum") {
+        if !options.iter().any(|option| compare::equals(option, value)) {
+            return false;
+        }
+    }
+
+    match value {
+        Bson::Document(document) => validate_document(schema, document),
+        Bson::Array(elements) => match schema.get_document("items") {
+            Ok(items) => {
+                elements.iter().all(|element| validate(items, element))
+            }
+            Err(_) => true,
+        },
+        _ => true,
+    }
+}
+
+fn validate_document(schema: &Document, document: &Document) -> bool {
+    if let Ok(required) = schema.get_array("required") {
+        let missing = required.iter().any(|field| match field {
+            Bson::String(field) => !document.contains_key(field),
+            _ => false,
+        });
+
+        if missing {
+            return false;
+        }
+    }
+
+    let properties = schema.get_document("properties").ok();
+
+    document.iter().all(|(key, value)| {
+        match properties
+            .and_then(|properties| properties.get_document(key).ok())
+        {
+            Some(property) => validate(property, value),
+            None => match schema.get("additionalProperties") {
+                Some(Bson::Boolean(allowed)) => *allowed,
+                Some(Bson::Document(additional)) => validate(additional, value),
+                _ => true,
+            },
+        }
+    })
+}
@@ -0,0 +1,124 @@
+use bson::{Bson, Document};
+
+use super::EvalError;
+
+// Without a text index every string in the document is searched, and no
+// stemming or stop words are applied.
+pub(crate) struct TextSearch {
+    terms: Vec<String>,
+    phrases: Vec<String>,
+    negated: Vec<String>,
+    case_sensitive: bool,
+}
+
+impl TextSearch {
+    pub(crate) fn compile(value: &Bson) -> Result<Self, EvalError> {
+        let Bson::Document(text) = value else {
+            return Err(EvalError::invalid("$text", "expects a document"));
+        };
+
+        let query = text
+            .get_str("$search")
+            .map_err(|_| EvalError::invalid("$text", "$search is required"))?;
+        let case_sensitive = text.get_bool("$caseSensitive").unwrap_or(false);
+
+        let fold = |term: &str| {
+            if case_sensitive {
+                term.to_owned()
+            } else {
+                term.to_lowercase()
+            }
+        };
+
+        let mut search = Self {
+            terms: Vec::new(),
+            phrases: Vec::new(),
+            negated: Vec::new(),
+            case_sensitive,
+        };
+
+        for (index, part) in query.split('"').enumerate() {
+            // Odd parts sit between quotes.
+            if index % 2 == 1 {
+                if !part.trim().is_empty() {
+                    search.phrases.push(fold(part));
+                }
+                continue;
+            }
+
+            for term in part.split_whitespace() {
+                let (terms, term) = match term.strip_prefix('-') {
+                    Some(negated) => (&mut search.negated, negated),
+                    None => (&mut search.terms, term),
+                };
+
+                terms.extend(words(&fold(term)).into_iter().map(str::to_owned));
+            }
+        }
+
+        Ok(search)
+    }
+
+    pub(crate) fn matches(&self, document: &Document) -> bool {
+        let mut strings = Vec::new();
+        for (_, value) in document {
+            collect_strings(value, &mut strings);
+        }
+
+        let strings = strings
+            .into_iter()
+            .map(|string| {
+                if self.case_sensitive {
+                    string.to_owned()
+                } else {
+                    string.to_lowercase()
+                }
+            })
+            .collect::<Vec<_>>();
+        let words = strings
+            .iter()
+            .flat_map(|string| words(string))
+            .collect::<Vec<_>>();
+
+        if self
+            .negated
+            .iter()
+            .any(|term| words.contains(&term.as_str()))
+        {
+            return false;
+        }
+
+        if !self.phrases.is_empty() {
+            return self.phrases.iter().all(|phrase| {
+                strings
+                    .iter()
+                    .any(|string| string.contains(phrase.as_str()))
+            });
+        }
+
+        self.terms.iter().any(|term| words.contains(&term.as_str()))
+    }
+}
+
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn collect_strings<'a>(value: &'a Bson, strings: &mut Vec<&'a str>) {
+    match value {
+        Bson::String(string) => strings.push(string),
+        Bson::Array(elements) => {
+            for element in elements {
+                collect_strings(element, strings);
+            }
+        }
+        Bson::Document(document) => {
+            for (_, value) in document {
+                collect_strings(value, strings);
+            }
+        }
+        _ => {}
+    }
+}
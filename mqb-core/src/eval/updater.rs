@@ -0,0 +1,630 @@
+use std::{cmp::Ordering, marker::PhantomData};
+
+use bson::{Bson, Document};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{update::overlaps, UpdateBuilder};
+
+use super::{
+    arithmetic, compare,
+    matcher::{is_value_filter, Predicate},
+    path, EvalError, Matcher,
+};
+
+const OPERATORS: &[&str] = &[
+    "$set",
+    "$setOnInsert",
+    "$unset",
+    "$rename",
+    "$inc",
+    "$mul",
+    "$min",
+    "$max",
+    "$push",
+    "$addToSet",
+    "$pull",
+    "$pullAll",
+    "$pop",
+    "$currentDate",
+];
+
+pub struct Updater<T> {
+    document: Document,
+    array_filters: Vec<(String, Predicate)>,
+    upsert: bool,
+    clock: Box<dyn Fn() -> bson::DateTime + Send + Sync>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Updater<T> {
+    pub fn new(update: UpdateBuilder<T>) -> Result<Self, EvalError> {
+        let (document, array_filters) = update
+            .try_build_with_array_filters()
+            .map_err(EvalError::Build)?;
+
+        for (operator, fields) in &document {
+            if !OPERATORS.contains(&operator.as_str()) {
+                return Err(EvalError::Unsupported(operator.clone()));
+            }
+
+            if !matches!(fields, Bson::Document(_)) {
+                return Err(EvalError::invalid(operator, "expects a document"));
+            }
+        }
+
+        let array_filters = array_filters
+            .iter()
+            .map(|filter| {
+                let identifier =
+                    filter_identifier(filter).ok_or_else(|| {
+                        EvalError::invalid(
+                            "arrayFilters",
+                            "names no identifier",
+                        )
+                    })?;
+
+                Ok((identifier, Predicate::compile(filter)?))
+            })
+            .collect::<Result<_, EvalError>>()?;
+
+        Ok(Self {
+            document,
+            array_filters,
+            upsert: false,
+            clock: Box::new(bson::DateTime::now),
+            marker: PhantomData,
+        })
+    }
+
+    // `$setOnInsert` only applies when the update inserts a new document.
+    pub fn upsert(mut self, upsert: bool) -> Self {
+        self.upsert = upsert;
+        self
+    }
+
+    pub fn clock(
+        mut self,
+        clock: impl Fn() -> bson::DateTime + Send + Sync + 'static,
+    ) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    // The document is left untouched when any operator fails.
+    pub fn apply_to_document(
+        &self,
+        document: &mut Document,
+    ) -> Result<(), EvalError> {
+        self.apply_positional(document, None)
+    }
+
+    // Updates using `first_match` key paths need the query which selected
+    // the document to know which element `$` stands for.
+    pub fn apply_to_matched_document(
+        &self,
+        document: &mut Document,
+        matcher: &Matcher<T>,
+    ) -> Result<(), EvalError> {
+        self.apply_positional(document, Some(matcher))
+    }
+
+    fn apply_positional(
+        &self,
+        document: &mut Document,
+        matcher: Option<&Matcher<T>>,
+    ) -> Result<(), EvalError> {
+        let mut updated = document.clone();
+        let mut expanded = Vec::<String>::new();
+
+        for (operator, fields) in &self.document {
+            let Bson::Document(fields) = fields else {
+                continue;
+            };
+
+            for (path, operand) in fields {
+                let error = |message| {
+                    EvalError::invalid(
+                        operator,
+                        format!("{}: {}", path, message),
+                    )
+                };
+
+                for path in self
+                    .expand_path(document, operator, path, matcher)
+                    .map_err(error)?
+                {
+                    // The element selected by two positional paths is only
+                    // known once they are expanded.
+                    if expanded.iter().any(|other| overlaps(&path, other)) {
+                        return Err(EvalError::invalid(
+                            operator,
+                            format!("Update created a conflict at '{}'", path),
+                        ));
+                    }
+
+                    self.apply_operator(&mut updated, operator, &path, operand)
+                        .map_err(error)?;
+                    expanded.push(path);
+                }
+            }
+        }
+
+        if let Some(id) = document.get("_id") {
+            if updated.get("_id").is_none_or(|updated| updated != id) {
+                return Err(EvalError::invalid(
+                    "_id",
+                    "the immutable field '_id' would be modified",
+                ));
+            }
+        }
+
+        *document = updated;
+
+        Ok(())
+    }
+
+    // Positional segments are replaced by the indices of the elements they
+    // select, looked up in the document as it was before the update.
+    fn expand_path(
+        &self,
+        document: &Document,
+        operator: &str,
+        path: &str,
+        matcher: Option<&Matcher<T>>,
+    ) -> Result<Vec<String>, String> {
+        let mut paths = vec![String::new()];
+
+        for segment in path.split('.') {
+            if !segment.starts_with('$') {
+                for path in &mut paths {
+                    push_segment(path, segment);
+                }
+                continue;
+            }
+
+            if operator == "$rename" {
+                return Err(
+                    "cannot rename through a positional path".to_owned()
+                );
+            }
+
+            let mut expanded = Vec::new();
+            for prefix in paths {
+                let indices =
+                    self.positions(document, &prefix, segment, matcher)?;
+
+                expanded.extend(indices.into_iter().map(|index| {
+                    let mut path = prefix.clone();
+                    push_segment(&mut path, &index.to_string());
+                    path
+                }));
+            }
+
+            paths = expanded;
+        }
+
+        Ok(paths)
+    }
+
+    fn positions(
+        &self,
+        document: &Document,
+        array_path: &str,
+        segment: &str,
+        matcher: Option<&Matcher<T>>,
+    ) -> Result<Vec<usize>, String> {
+        if segment == "$" {
+            let matcher = matcher.ok_or_else(|| {
+                "the positional operator needs the matching query".to_owned()
+            })?;
+
+            return match matcher.first_match(document, array_path) {
+                Some(index) => Ok(vec![index]),
+                None => Err("the positional operator did not find the match \
+                             needed from the query"
+                    .to_owned()),
+            };
+        }
+
+        let Some(identifier) = segment
+            .strip_prefix("$[")
+            .and_then(|segment| segment.strip_suffix(']'))
+        else {
+            return Err(format!("unknown positional segment {}", segment));
+        };
+
+        let Some(Bson::Array(elements)) = path::get(document, array_path)
+        else {
+            return Err(format!(
+                "the path '{}' must be an array to apply array updates",
+                array_path
+            ));
+        };
+
+        if identifier.is_empty() {
+            return Ok((0..elements.len()).collect());
+        }
+
+        let Some((_, predicate)) = self
+            .array_filters
+            .iter()
+            .find(|(filtered, _)| filtered == identifier)
+        else {
+            return Err(format!(
+                "no array filter for identifier '{}'",
+                identifier
+            ));
+        };
+
+        Ok(elements
+            .iter()
+            .enumerate()
+            .filter(|(_, element)| {
+                predicate
+                    .matches(&bson::doc! { identifier: (*element).clone() })
+            })
+            .map(|(index, _)| index)
+            .collect())
+    }
+
+    fn apply_operator(
+        &self,
+        document: &mut Document,
+        operator: &str,
+        path: &str,
+        operand: &Bson,
+    ) -> Result<(), String> {
+        match operator {
+            "$set" => path::set(document, path, operand.clone()),
+            "$setOnInsert" if self.upsert => {
+                path::set(document, path, operand.clone())
+            }
+            "$setOnInsert" => Ok(()),
+            "$unset" => {
+                path::remove(document, path);
+                Ok(())
+            }
+            "$rename" => {
+                let Some(target) = operand.as_str() else {
+                    return Err("expects a target path".to_owned());
+                };
+
+                let Some(value) = path::get_mut(document, path).cloned() else {
+                    return Ok(());
+                };
+
+                path::remove(document, path);
+                path::set(document, target, value)
+            }
+            "$inc" => match path::get_mut(document, path) {
+                Some(current) => {
+                    *current = arithmetic::add(current, operand)?;
+                    Ok(())
+                }
+                None => path::set(document, path, operand.clone()),
+            },
+            "$mul" => match path::get_mut(document, path) {
+                Some(current) => {
+                    *current = arithmetic::multiply(current, operand)?;
+                    Ok(())
+                }
+                None => path::set(document, path, arithmetic::zero(operand)?),
+            },
+            "$min" => replace_if(document, path, operand, Ordering::Less),
+            "$max" => replace_if(document, path, operand, Ordering::Greater),
+            "$push" => push(document, path, operand),
+            "$addToSet" => {
+                let values = each(operand, &[])?;
+                let elements = array_mut(document, path)?;
+
+                for value in values {
+                    if !elements
+                        .iter()
+                        .any(|element| compare::equals(element, &value))
+                    {
+                        elements.push(value);
+                    }
+                }
+
+                Ok(())
+            }
+            "$pull" => {
+                let condition = PullCondition::compile(operand)?;
+                remove_elements(document, path, |element| {
+                    condition.matches(element)
+                })
+            }
+            "$pullAll" => {
+                let Bson::Array(values) = operand else {
+                    return Err("expects an array".to_owned());
+                };
+
+                remove_elements(document, path, |element| {
+                    values.iter().any(|value| compare::equals(element, value))
+                })
+            }
+            "$pop" => {
+                let Some(Bson::Array(elements)) = path::get_mut(document, path)
+                else {
+                    return Ok(());
+                };
+
+                match compare::as_i64(operand) {
+                    Some(-1) if !elements.is_empty() => {
+                        elements.remove(0);
+                    }
+                    Some(1) => {
+                        elements.pop();
+                    }
+                    Some(-1) => {}
+                    _ => return Err("expects 1 or -1".to_owned()),
+                }
+
+                Ok(())
+            }
+            "$currentDate" => {
+                let now = (self.clock)();
+                let value = match operand {
+                    Bson::Document(spec) => match spec.get_str("$type") {
+                        Ok("timestamp") => Bson::Timestamp(bson::Timestamp {
+                            time: (now.timestamp_millis() / 1000) as u32,
+                            increment: 1,
+                        }),
+                        Ok("date") => Bson::DateTime(now),
+                        _ => return Err("unknown $type".to_owned()),
+                    },
+                    _ => Bson::DateTime(now),
+                };
+
+                path::set(document, path, value)
+            }
+            operator => Err(format!("{} is not supported", operator)),
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Updater<T> {
+    pub fn apply(&self, value: &mut T) -> Result<(), EvalError> {
+        let mut document =
+            bson::to_document(value).map_err(EvalError::Serialization)?;
+
+        self.apply_to_document(&mut document)?;
+
+        *value = bson::from_document(document)
+            .map_err(EvalError::Deserialization)?;
+
+        Ok(())
+    }
+}
+
+impl<T> TryFrom<UpdateBuilder<T>> for Updater<T> {
+    type Error = EvalError;
+
+    fn try_from(update: UpdateBuilder<T>) -> Result<Self, Self::Error> {
+        Self::new(update)
+    }
+}
+
+fn push_segment(path: &mut String, segment: &str) {
+    if !path.is_empty() {
+        path.push('.');
+    }
+    path.push_str(segment);
+}
+
+// Array filters are written against `identifier.field`, so the identifier
+// is the first segment of their paths.
+fn filter_identifier(filter: &Document) -> Option<String> {
+    filter
+        .iter()
+        .find_map(|(key, value)| match (key.as_str(), value) {
+            ("$and" | "$or" | "$nor", Bson::Array(clauses)) => {
+                clauses.iter().find_map(|clause| match clause {
+                    Bson::Document(clause) => filter_identifier(clause),
+                    _ => None,
+                })
+            }
+            (key, _) if key.starts_with('$') => None,
+            (key, _) => key.split('.').next().map(str::to_owned),
+        })
+}
+
+// `$min`/`$max` only write when the operand orders before/after the
+// current value, and always write when the field is missing.
+fn replace_if(
+    document: &mut Document,
+    path: &str,
+    operand: &Bson,
+    ordering: Ordering,
+) -> Result<(), String> {
+    match path::get_mut(document, path) {
+        Some(current) => {
+            if compare::compare(operand, current) == ordering {
+                *current = operand.clone();
+            }
+            Ok(())
+        }
+        None => path::set(document, path, operand.clone()),
+    }
+}
+
+// `$pull` takes either a value, conditions on the element itself, or a
+// query over the fields of subdocument elements.
+enum PullCondition {
+    Value(Bson),
+    Element(Predicate),
+    Fields(Predicate),
+}
+
+impl PullCondition {
+    fn compile(operand: &Bson) -> Result<Self, String> {
+        match operand {
+            Bson::Document(conditions) if is_value_filter(conditions) => {
+                Predicate::compile(&bson::doc! { "element": conditions })
+                    .map(PullCondition::Element)
+                    .map_err(|e| e.to_string())
+            }
+            Bson::Document(query) => Predicate::compile(query)
+                .map(PullCondition::Fields)
+                .map_err(|e| e.to_string()),
+            value => Ok(PullCondition::Value(value.clone())),
+        }
+    }
+
+    fn matches(&self, element: &Bson) -> bool {
+        match self {
+            PullCondition::Value(value) => compare::equals(element, value),
+            PullCondition::Element(predicate) => {
+                predicate.matches(&bson::doc! { "element": element.clone() })
+            }
+            PullCondition::Fields(predicate) => match element {
+                Bson::Document(element) => predicate.matches(element),
+                _ => false,
+            },
+        }
+    }
+}
+
+fn remove_elements(
+    document: &mut Document,
+    path: &str,
+    remove: impl Fn(&Bson) -> bool,
+) -> Result<(), String> {
+    match path::get_mut(document, path) {
+        Some(Bson::Array(elements)) => {
+            elements.retain(|element| !remove(element));
+            Ok(())
+        }
+        Some(current) => Err(format!(
+            "cannot remove from a value of type {}",
+            compare::type_alias(current)
+        )),
+        None => Ok(()),
+    }
+}
+
+fn each(operand: &Bson, modifiers: &[&str]) -> Result<Vec<Bson>, String> {
+    match operand {
+        Bson::Document(operand) if operand.contains_key("$each") => {
+            if let Some(modifier) = operand.keys().find(|modifier| {
+                *modifier != "$each" && !modifiers.contains(&modifier.as_str())
+            }) {
+                return Err(format!("{} is not supported", modifier));
+            }
+
+            match operand.get("$each") {
+                Some(Bson::Array(values)) => Ok(values.clone()),
+                _ => Err("$each expects an array".to_owned()),
+            }
+        }
+        value => Ok(vec![value.clone()]),
+    }
+}
+
+// Modifiers apply in the server's order: insert at `$position`, then
+// `$sort` the whole array, then `$slice` it.
+fn push(
+    document: &mut Document,
+    path: &str,
+    operand: &Bson,
+) -> Result<(), String> {
+    let values = each(operand, &["$position", "$sort", "$slice"])?;
+    let modifiers = match operand {
+        Bson::Document(operand) => operand.clone(),
+        _ => Document::new(),
+    };
+
+    let elements = array_mut(document, path)?;
+
+    let index = match modifiers.get("$position") {
+        Some(position) => {
+            let position = compare::as_i64(position)
+                .ok_or_else(|| "$position expects an integer".to_owned())?;
+            let len = elements.len() as i64;
+
+            if position < 0 {
+                (len + position).max(0) as usize
+            } else {
+                position.min(len) as usize
+            }
+        }
+        None => elements.len(),
+    };
+    elements.splice(index..index, values);
+
+    match modifiers.get("$sort") {
+        Some(Bson::Document(fields)) => elements.sort_by(|lhs, rhs| {
+            fields
+                .iter()
+                .map(|(field, direction)| {
+                    let ordering = compare::compare(
+                        &sort_key(lhs, field),
+                        &sort_key(rhs, field),
+                    );
+                    if compare::as_i64(direction) == Some(-1) {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        }),
+        Some(direction) => match compare::as_i64(direction) {
+            Some(1) => elements.sort_by(compare::compare),
+            Some(-1) => elements.sort_by(|lhs, rhs| compare::compare(rhs, lhs)),
+            _ => return Err("$sort expects 1, -1 or a document".to_owned()),
+        },
+        None => {}
+    }
+
+    if let Some(slice) = modifiers.get("$slice") {
+        let slice = compare::as_i64(slice)
+            .ok_or_else(|| "$slice expects an integer".to_owned())?;
+
+        if slice >= 0 {
+            elements.truncate(slice as usize);
+        } else {
+            let keep = slice.unsigned_abs() as usize;
+            let skip = elements.len().saturating_sub(keep);
+            elements.drain(..skip);
+        }
+    }
+
+    Ok(())
+}
+
+fn sort_key(element: &Bson, field: &str) -> Bson {
+    match element {
+        Bson::Document(element) => path::lookup(element, field)
+            .into_iter()
+            .flatten()
+            .next()
+            .cloned()
+            .unwrap_or(Bson::Null),
+        _ => Bson::Null,
+    }
+}
+
+// Creates the array when the field is missing.
+fn array_mut<'a>(
+    document: &'a mut Document,
+    path: &str,
+) -> Result<&'a mut Vec<Bson>, String> {
+    if path::get_mut(document, path).is_none() {
+        path::set(document, path, Bson::Array(Vec::new()))?;
+    }
+
+    match path::get_mut(document, path) {
+        Some(Bson::Array(elements)) => Ok(elements),
+        Some(current) => Err(format!(
+            "cannot append to a value of type {}",
+            compare::type_alias(current)
+        )),
+        None => Err("cannot create the array".to_owned()),
+    }
+}
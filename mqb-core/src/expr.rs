@@ -0,0 +1,141 @@
+use std::marker::PhantomData;
+
+use bson::Bson;
+
+use crate::{kp::KeyPathNonInitialNodeLike, Error, Errors};
+
+pub struct Operand<T, V> {
+    bson: Result<Bson, Vec<Error>>,
+    marker: PhantomData<(T, V)>,
+}
+
+impl<T, V> Operand<T, V> {
+    pub fn field<KP>(kp: KP) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = V>,
+    {
+        Self {
+            bson: Ok(Bson::String(format!("${}", crate::kp::render(&kp)))),
+            marker: PhantomData,
+        }
+    }
+
+    // Literals go through the key path's serializer, so they compare with
+    // the field as it is stored, even when it uses `serialize_with`.
+    pub fn value_for<KP>(kp: KP, value: impl Into<V>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = V>,
+    {
+        let serializer = kp.serializer();
+
+        Self {
+            bson: serializer(&value.into())
+                .map(|bson| Bson::Document(bson::doc! { "$literal": bson }))
+                .map_err(|e| {
+                    vec![Error::serialization(
+                        Some(crate::kp::render(&kp)),
+                        "$literal",
+                        e,
+                    )]
+                }),
+            marker: PhantomData,
+        }
+    }
+}
+
+pub struct Expr<T> {
+    bson: Result<Bson, Vec<Error>>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Expr<T> {
+    pub fn eq<V: PartialEq>(lhs: Operand<T, V>, rhs: Operand<T, V>) -> Self {
+        Self::comparison(lhs, rhs, "$eq")
+    }
+
+    pub fn ne<V: PartialEq>(lhs: Operand<T, V>, rhs: Operand<T, V>) -> Self {
+        Self::comparison(lhs, rhs, "$ne")
+    }
+
+    pub fn gt<V: PartialOrd>(lhs: Operand<T, V>, rhs: Operand<T, V>) -> Self {
+        Self::comparison(lhs, rhs, "$gt")
+    }
+
+    pub fn gte<V: PartialOrd>(lhs: Operand<T, V>, rhs: Operand<T, V>) -> Self {
+        Self::comparison(lhs, rhs, "$gte")
+    }
+
+    pub fn lt<V: PartialOrd>(lhs: Operand<T, V>, rhs: Operand<T, V>) -> Self {
+        Self::comparison(lhs, rhs, "$lt")
+    }
+
+    pub fn lte<V: PartialOrd>(lhs: Operand<T, V>, rhs: Operand<T, V>) -> Self {
+        Self::comparison(lhs, rhs, "$lte")
+    }
+
+    pub fn and(exprs: impl IntoIterator<Item = Expr<T>>) -> Self {
+        Self::logical(exprs, "$and")
+    }
+
+    pub fn or(exprs: impl IntoIterator<Item = Expr<T>>) -> Self {
+        Self::logical(exprs, "$or")
+    }
+
+    pub fn try_build(self) -> Result<Bson, Errors> {
+        self.bson.map_err(Errors)
+    }
+}
+
+impl<T> std::ops::Not for Expr<T> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::logical([self], "$not")
+    }
+}
+
+impl<T> Expr<T> {
+    fn comparison<V>(
+        lhs: Operand<T, V>,
+        rhs: Operand<T, V>,
+        op: &'static str,
+    ) -> Self {
+        let bson = match (lhs.bson, rhs.bson) {
+            (Ok(lhs), Ok(rhs)) => {
+                Ok(Bson::Document(bson::doc! { op: [lhs, rhs] }))
+            }
+            (lhs, rhs) => {
+                Err(lhs.err().into_iter().chain(rhs.err()).flatten().collect())
+            }
+        };
+
+        Self {
+            bson,
+            marker: PhantomData,
+        }
+    }
+
+    fn logical(
+        exprs: impl IntoIterator<Item = Expr<T>>,
+        op: &'static str,
+    ) -> Self {
+        let mut bsons = Vec::new();
+        let mut errors = Vec::new();
+
+        for expr in exprs {
+            match expr.bson {
+                Ok(bson) => bsons.push(bson),
+                Err(e) => errors.extend(e),
+            }
+        }
+
+        Self {
+            bson: if errors.is_empty() {
+                Ok(Bson::Document(bson::doc! { op: bsons }))
+            } else {
+                Err(errors)
+            },
+            marker: PhantomData,
+        }
+    }
+}
@@ -1,9 +1,27 @@
-use crate::kp::KeyPathNonInitialNodeLike;
+use num_traits::PrimInt;
+
+use crate::{
+    expr::Expr,
+    geo::{Area, Geometry, Point},
+    kp::{KeyPathNonInitialNodeLike, KeyPathableAsRoot},
+    BitMask, BsonType, ElementFilter, Error, Errors, JsonSchema, RegexOptions,
+    RegexTarget,
+};
+
+// Controls what happens when an operator is applied twice to the same key
+// path. The mode is inherited by builders created for nested clauses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FilterMode {
+    #[default]
+    Strict,
+    Lenient,
+}
 
 #[derive(Default)]
 pub struct FilterBuilder<T> {
     document: bson::Document,
-    error: Option<bson::ser::Error>,
+    errors: Vec<Error>,
+    mode: FilterMode,
     marker: std::marker::PhantomData<T>,
 }
 
@@ -11,17 +29,33 @@ impl<T> FilterBuilder<T> {
     pub fn new() -> Self {
         Self {
             document: bson::Document::new(),
-            error: None,
+            errors: Vec::new(),
+            mode: FilterMode::default(),
             marker: std::marker::PhantomData,
         }
     }
 
-    pub fn try_build(self) -> Result<bson::Document, bson::ser::Error> {
-        if let Some(error) = self.error {
-            Err(error)
-        } else {
-            Ok(self.document)
+    pub fn mode(mut self, mode: FilterMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn try_build(self) -> Result<bson::Document, Errors> {
+        Errors::check(self.errors, self.document)
+    }
+
+    // `$text` relies on the collection's text index, so builders nested
+    // under a key path or a `$nor` can't hold one.
+    pub(crate) fn try_build_nested(mut self) -> Result<bson::Document, Errors> {
+        if self.document.contains_key("$text") {
+            self.errors.push(Error::invalid(
+                None,
+                "$text",
+                "only allowed at the top level of a query or in $and/$or",
+            ));
         }
+
+        self.try_build()
     }
 }
 
@@ -68,77 +102,729 @@ impl<T> FilterBuilder<T> {
         self.op(kp, value, "$lte")
     }
 
-    pub fn exists<const EXISTS: bool, KP, V>(mut self, kp: KP) -> Self
+    pub fn exists<const EXISTS: bool, KP, V>(self, kp: KP) -> Self
     where
         KP: KeyPathNonInitialNodeLike<Origin = T, Current = Option<V>>,
     {
         let keypath = crate::kp::render(&kp);
 
-        if self.document.get_document(&keypath).is_err() {
-            self.document.insert(&keypath, bson::Document::new());
+        self.insert_operators(keypath, bson::doc! { "$exists": EXISTS })
+    }
+
+    pub fn type_is<KP>(self, kp: KP, ty: BsonType) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T>,
+    {
+        let keypath = crate::kp::render(&kp);
+
+        self.insert_operators(keypath, bson::doc! { "$type": ty })
+    }
+
+    pub fn modulo<KP>(mut self, kp: KP, divisor: i64, remainder: i64) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: PrimInt>,
+    {
+        let keypath = crate::kp::render(&kp);
+
+        if divisor == 0 {
+            self.errors.push(Error::invalid(
+                Some(keypath),
+                "$mod",
+                "the divisor cannot be zero",
+            ));
+            return self;
+        }
+
+        self.insert_operators(
+            keypath,
+            bson::doc! { "$mod": vec![divisor, remainder] },
+        )
+    }
+
+    pub fn bits_all_set<KP>(self, kp: KP, mask: impl Into<BitMask>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: PrimInt>,
+    {
+        self.bits_op(kp, mask.into(), "$bitsAllSet")
+    }
+
+    pub fn bits_any_set<KP>(self, kp: KP, mask: impl Into<BitMask>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: PrimInt>,
+    {
+        self.bits_op(kp, mask.into(), "$bitsAnySet")
+    }
+
+    pub fn bits_all_clear<KP>(self, kp: KP, mask: impl Into<BitMask>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: PrimInt>,
+    {
+        self.bits_op(kp, mask.into(), "$bitsAllClear")
+    }
+
+    pub fn bits_any_clear<KP>(self, kp: KP, mask: impl Into<BitMask>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: PrimInt>,
+    {
+        self.bits_op(kp, mask.into(), "$bitsAnyClear")
+    }
+
+    pub fn r#in<KP, V>(
+        self,
+        kp: KP,
+        values: impl IntoIterator<Item = impl Into<V>>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = V>,
+    {
+        self.values_op(kp, values, "$in")
+    }
+
+    pub fn nin<KP, V>(
+        self,
+        kp: KP,
+        values: impl IntoIterator<Item = impl Into<V>>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = V>,
+    {
+        self.values_op(kp, values, "$nin")
+    }
+
+    pub fn regex<KP>(
+        self,
+        kp: KP,
+        pattern: impl Into<String>,
+        options: RegexOptions,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: RegexTarget>,
+    {
+        let keypath = crate::kp::render(&kp);
+        let mut regex = bson::doc! { "$regex": pattern.into() };
+
+        let options = options.render();
+        if !options.is_empty() {
+            regex.insert("$options", options);
         }
 
-        let exists = self.document.get_document_mut(&keypath).unwrap();
-        exists.insert("$exists", EXISTS);
+        self.insert_operators(keypath, regex)
+    }
+}
+
+pub trait FilterClause<T> {
+    fn into_filter(self, filter: FilterBuilder<T>) -> FilterBuilder<T>;
+}
+
+impl<T> FilterClause<T> for FilterBuilder<T> {
+    fn into_filter(self, _: FilterBuilder<T>) -> FilterBuilder<T> {
+        self
+    }
+}
+
+impl<T, F> FilterClause<T> for F
+where
+    F: FnOnce(FilterBuilder<T>) -> FilterBuilder<T>,
+{
+    fn into_filter(self, filter: FilterBuilder<T>) -> FilterBuilder<T> {
+        self(filter)
+    }
+}
 
+// Collects clauses of different types, such as several capturing closures,
+// which can't share an array.
+pub struct FilterClauses<T> {
+    filters: Vec<FilterBuilder<T>>,
+    mode: FilterMode,
+}
+
+impl<T> FilterClauses<T> {
+    pub fn clause(mut self, clause: impl FilterClause<T>) -> Self {
+        let filter = clause.into_filter(FilterBuilder::new().mode(self.mode));
+        self.filters.push(filter);
         self
     }
+}
 
-    pub fn r#in<KP, V>(mut self, kp: KP, values: Vec<V>) -> Self
+impl<T> FilterBuilder<T> {
+    pub fn and<C>(self, clauses: impl IntoIterator<Item = C>) -> Self
+    where
+        C: FilterClause<T>,
+    {
+        self.logical(clauses, "$and")
+    }
+
+    pub fn or<C>(self, clauses: impl IntoIterator<Item = C>) -> Self
+    where
+        C: FilterClause<T>,
+    {
+        self.logical(clauses, "$or")
+    }
+
+    pub fn nor<C>(self, clauses: impl IntoIterator<Item = C>) -> Self
+    where
+        C: FilterClause<T>,
+    {
+        self.logical(clauses, "$nor")
+    }
+
+    pub fn and_with(
+        self,
+        clauses: impl FnOnce(FilterClauses<T>) -> FilterClauses<T>,
+    ) -> Self {
+        let clauses = clauses(self.clauses());
+        self.logical(clauses.filters, "$and")
+    }
+
+    pub fn or_with(
+        self,
+        clauses: impl FnOnce(FilterClauses<T>) -> FilterClauses<T>,
+    ) -> Self {
+        let clauses = clauses(self.clauses());
+        self.logical(clauses.filters, "$or")
+    }
+
+    pub fn nor_with(
+        self,
+        clauses: impl FnOnce(FilterClauses<T>) -> FilterClauses<T>,
+    ) -> Self {
+        let clauses = clauses(self.clauses());
+        self.logical(clauses.filters, "$nor")
+    }
+
+    fn clauses(&self) -> FilterClauses<T> {
+        FilterClauses {
+            filters: Vec::new(),
+            mode: self.mode,
+        }
+    }
+}
+
+impl<T> FilterBuilder<T> {
+    pub fn all<KP, V>(
+        mut self,
+        kp: KP,
+        values: impl IntoIterator<Item = impl Into<V>>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = Vec<V>>,
+    {
+        let values = values.into_iter().map(Into::into).collect::<Vec<_>>();
+        let keypath = crate::kp::render(&kp);
+
+        let bson_values = match serialize_elements(&kp, &values) {
+            Ok(bson) => bson,
+            Err(e) => {
+                self.errors.push(Error::serialization(
+                    Some(keypath),
+                    "$all",
+                    e,
+                ));
+                return self;
+            }
+        };
+
+        self.insert_operators(keypath, bson::doc! { "$all": bson_values })
+    }
+
+    pub fn contains<KP, V>(mut self, kp: KP, item: impl Into<V>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = Vec<V>>,
+    {
+        let keypath = crate::kp::render(&kp);
+
+        // The field's serializer takes the whole Vec, so the item goes
+        // through it as a single element array.
+        let bson = match serialize_elements(&kp, &vec![item.into()]) {
+            Ok(elements) => match elements.into_iter().next() {
+                Some(bson) => bson,
+                None => {
+                    self.errors.push(Error::invalid(
+                        Some(keypath),
+                        "$eq",
+                        "the item serialized to an empty array",
+                    ));
+                    return self;
+                }
+            },
+            Err(e) => {
+                self.errors
+                    .push(Error::serialization(Some(keypath), "$eq", e));
+                return self;
+            }
+        };
+
+        self.insert_operators(keypath, bson::doc! { "$eq": bson })
+    }
+
+    pub fn size<KP, V>(self, kp: KP, size: usize) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = Vec<V>>,
+    {
+        let keypath = crate::kp::render(&kp);
+
+        self.insert_operators(keypath, bson::doc! { "$size": size as i64 })
+    }
+
+    pub fn elem_match<KP, V>(
+        mut self,
+        kp: KP,
+        element: impl FnOnce(FilterBuilder<V>) -> FilterBuilder<V>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = Vec<V>>,
+    {
+        let keypath = crate::kp::render(&kp);
+
+        let document = match element(self.nested()).try_build_nested() {
+            Ok(document) => document,
+            Err(errors) => {
+                self.errors
+                    .extend(errors.into_iter().map(|e| e.within(&keypath)));
+                return self;
+            }
+        };
+
+        self.insert_operators(keypath, bson::doc! { "$elemMatch": document })
+    }
+}
+
+impl<T> FilterBuilder<T> {
+    // `$elemMatch` with conditions on the elements themselves, for arrays of
+    // scalars.
+    pub fn elem_match_value<KP, V>(
+        mut self,
+        kp: KP,
+        element: impl FnOnce(ElementFilter<V>) -> ElementFilter<V>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current = Vec<V>>,
+    {
+        let keypath = crate::kp::render(&kp);
+
+        let document =
+            match element(ElementFilter::new(kp.serializer())).try_build() {
+                Ok(document) => document,
+                Err(errors) => {
+                    self.errors
+                        .extend(errors.into_iter().map(|e| e.within(&keypath)));
+                    return self;
+                }
+            };
+
+        self.insert_operators(keypath, bson::doc! { "$elemMatch": document })
+    }
+}
+
+impl<T> FilterBuilder<T> {
+    pub fn geo_within<KP>(self, kp: KP, area: impl Area) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: Geometry>,
+    {
+        self.geometry_op(kp, area, "$geoWithin", None, None)
+    }
+
+    pub fn geo_intersects<KP>(self, kp: KP, geometry: impl Geometry) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: Geometry>,
+    {
+        self.geometry_op(kp, geometry, "$geoIntersects", None, None)
+    }
+
+    pub fn near<KP>(
+        self,
+        kp: KP,
+        point: Point,
+        max_distance: Option<f64>,
+        min_distance: Option<f64>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: Geometry>,
+    {
+        self.geometry_op(kp, point, "$near", max_distance, min_distance)
+    }
+
+    pub fn near_sphere<KP>(
+        self,
+        kp: KP,
+        point: Point,
+        max_distance: Option<f64>,
+        min_distance: Option<f64>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T, Current: Geometry>,
+    {
+        self.geometry_op(kp, point, "$nearSphere", max_distance, min_distance)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextSearchOptions {
+    pub language: Option<String>,
+    pub case_sensitive: bool,
+    pub diacritic_sensitive: bool,
+}
+
+impl<T: KeyPathableAsRoot> FilterBuilder<T> {
+    pub fn text(
+        mut self,
+        search: impl Into<String>,
+        options: TextSearchOptions,
+    ) -> Self {
+        let mut text = bson::doc! { "$search": search.into() };
+
+        if let Some(language) = options.language {
+            text.insert("$language", language);
+        }
+
+        if options.case_sensitive {
+            text.insert("$caseSensitive", true);
+        }
+
+        if options.diacritic_sensitive {
+            text.insert("$diacriticSensitive", true);
+        }
+
+        // A query can hold a single `$text`, whatever the mode.
+        if self.document.contains_key("$text") {
+            self.errors.push(Error::duplicate(None, "$text"));
+            return self;
+        }
+
+        self.document.insert("$text", text);
+
+        self
+    }
+}
+
+impl<T: JsonSchema> FilterBuilder<T> {
+    pub fn json_schema(self) -> Self {
+        self.insert_top_level("$jsonSchema", T::json_schema().into())
+    }
+}
+
+impl<T> FilterBuilder<T> {
+    pub fn expr(mut self, expr: Expr<T>) -> Self {
+        let bson = match expr.try_build() {
+            Ok(bson) => bson,
+            Err(errors) => {
+                self.errors.extend(errors);
+                return self;
+            }
+        };
+
+        // Only one `$expr` fits at the top level, later ones are kept as
+        // separate `$and` clauses.
+        if !self.document.contains_key("$expr") {
+            self.document.insert("$expr", bson);
+        } else if let Ok(and) = self.document.get_array_mut("$and") {
+            and.push(bson::doc! { "$expr": bson }.into());
+        } else {
+            self.document
+                .insert("$and", vec![bson::doc! { "$expr": bson }]);
+        }
+
+        self
+    }
+
+    pub fn not(
+        mut self,
+        negated: impl FnOnce(FilterBuilder<T>) -> FilterBuilder<T>,
+    ) -> Self {
+        let document = match negated(self.nested()).try_build() {
+            Ok(document) => document,
+            Err(errors) => {
+                self.errors.extend(errors);
+                return self;
+            }
+        };
+
+        for (keypath, operators) in document {
+            if keypath.starts_with('$') {
+                self.errors.push(Error::invalid(
+                    None,
+                    "$not",
+                    format!("cannot be applied to {}", keypath),
+                ));
+                return self;
+            }
+
+            // `$not` on a key path which is already negated is kept as a
+            // separate clause, merging both would negate their conjunction.
+            let negated = self
+                .document
+                .get_document(&keypath)
+                .is_ok_and(|existing| existing.contains_key("$not"));
+
+            if negated {
+                let mode = self.mode;
+                self = self.and([FilterBuilder {
+                    document: bson::doc! { keypath: { "$not": operators } },
+                    errors: Vec::new(),
+                    mode,
+                    marker: std::marker::PhantomData,
+                }]);
+                continue;
+            }
+
+            if self.document.get_document(&keypath).is_err() {
+                self.document.insert(&keypath, bson::Document::new());
+            }
+
+            let not = self.document.get_document_mut(&keypath).unwrap();
+            not.insert("$not", operators);
+        }
+
+        self
+    }
+}
+
+impl<T> FilterBuilder<T> {
+    fn nested<V>(&self) -> FilterBuilder<V> {
+        FilterBuilder::new().mode(self.mode)
+    }
+
+    // Top level operators follow the same rule as key path operators: a
+    // repeated one is reported, or kept as an `$and` clause when lenient.
+    fn insert_top_level(mut self, op: &str, bson: bson::Bson) -> Self {
+        if !self.document.contains_key(op) {
+            self.document.insert(op, bson);
+            return self;
+        }
+
+        match self.mode {
+            FilterMode::Strict => {
+                self.errors.push(Error::duplicate(None, op));
+            }
+            FilterMode::Lenient => {
+                let clause = bson::doc! { op: bson };
+                if let Ok(and) = self.document.get_array_mut("$and") {
+                    and.push(clause.into());
+                } else {
+                    self.document.insert("$and", vec![clause]);
+                }
+            }
+        }
+
+        self
+    }
+
+    // Inserting an operator which is already set on the key path would
+    // silently drop the earlier condition. Strict builders report it, lenient
+    // ones keep the new operators as a separate `$and` clause.
+    fn insert_operators(
+        mut self,
+        keypath: String,
+        operators: bson::Document,
+    ) -> Self {
+        let duplicates = match self.document.get_document(&keypath) {
+            Ok(existing) => operators
+                .keys()
+                .filter(|op| existing.contains_key(op))
+                .cloned()
+                .collect::<Vec<_>>(),
+            Err(_) => {
+                self.document.insert(&keypath, bson::Document::new());
+                Vec::new()
+            }
+        };
+
+        if duplicates.is_empty() {
+            let existing = self.document.get_document_mut(&keypath).unwrap();
+            existing.extend(operators);
+            return self;
+        }
+
+        match self.mode {
+            FilterMode::Strict => {
+                for op in duplicates {
+                    self.errors
+                        .push(Error::duplicate(Some(keypath.clone()), &op));
+                }
+            }
+            FilterMode::Lenient => {
+                let clause = bson::doc! { keypath: operators };
+
+                if let Ok(and) = self.document.get_array_mut("$and") {
+                    and.push(clause.into());
+                } else {
+                    self.document.insert("$and", vec![clause]);
+                }
+            }
+        }
+
+        self
+    }
+
+    fn geometry_op<KP>(
+        mut self,
+        kp: KP,
+        geometry: impl Geometry,
+        op: &'static str,
+        max_distance: Option<f64>,
+        min_distance: Option<f64>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T>,
+    {
+        let keypath = crate::kp::render(&kp);
+
+        let bson = match bson::to_bson(&geometry) {
+            Ok(bson) => bson,
+            Err(e) => {
+                self.errors.push(Error::serialization(Some(keypath), op, e));
+                return self;
+            }
+        };
+
+        let mut operand = bson::doc! { "$geometry": bson };
+
+        if let Some(max_distance) = max_distance {
+            operand.insert("$maxDistance", max_distance);
+        }
+
+        if let Some(min_distance) = min_distance {
+            operand.insert("$minDistance", min_distance);
+        }
+
+        self.insert_operators(keypath, bson::doc! { op: operand })
+    }
+
+    fn bits_op<KP>(self, kp: KP, mask: BitMask, op: &'static str) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T>,
+    {
+        let keypath = crate::kp::render(&kp);
+
+        self.insert_operators(keypath, bson::doc! { op: mask })
+    }
+
+    fn values_op<KP, V>(
+        mut self,
+        kp: KP,
+        values: impl IntoIterator<Item = impl Into<V>>,
+        op: &'static str,
+    ) -> Self
     where
         KP: KeyPathNonInitialNodeLike<Origin = T, Current = V>,
     {
         let serializer = kp.serializer();
+        let keypath = crate::kp::render(&kp);
 
         let bson_values = match values
             .into_iter()
-            .map(|value| serializer(&value))
+            .map(|value| serializer(&value.into()))
             .collect::<Result<Vec<_>, _>>()
         {
             Ok(bson) => bson,
             Err(e) => {
-                self.error = Some(e);
+                self.errors.push(Error::serialization(Some(keypath), op, e));
                 return self;
             }
         };
 
-        let keypath = crate::kp::render(&kp);
+        self.insert_operators(keypath, bson::doc! { op: bson_values })
+    }
+
+    fn logical<C>(
+        mut self,
+        clauses: impl IntoIterator<Item = C>,
+        op: &'static str,
+    ) -> Self
+    where
+        C: FilterClause<T>,
+    {
+        let mut documents = Vec::new();
 
-        if self.document.get_document(&keypath).is_err() {
-            self.document.insert(&keypath, bson::Document::new());
+        let mut failed = false;
+
+        for clause in clauses {
+            let filter = clause.into_filter(self.nested());
+            let built = if op == "$nor" {
+                filter.try_build_nested()
+            } else {
+                filter.try_build()
+            };
+
+            match built {
+                Ok(document) => documents.push(bson::Bson::from(document)),
+                Err(errors) => {
+                    self.errors.extend(errors);
+                    failed = true;
+                }
+            }
         }
 
-        let in_ = self.document.get_document_mut(&keypath).unwrap();
-        in_.insert("$in", bson_values);
+        if failed {
+            return self;
+        }
+
+        if documents.is_empty() {
+            self.errors.push(Error::invalid(
+                None,
+                op,
+                "requires at least one clause",
+            ));
+            return self;
+        }
+
+        // A second `$or`/`$nor` on the same builder must hold alongside the
+        // first one, so it is moved under `$and` instead of replacing it.
+        if op == "$and" || !self.document.contains_key(op) {
+            if let Ok(and) = self.document.get_array_mut(op) {
+                and.extend(documents);
+            } else {
+                self.document.insert(op, documents);
+            }
+        } else {
+            let clause = bson::doc! { op: documents };
+
+            if let Ok(and) = self.document.get_array_mut("$and") {
+                and.push(clause.into());
+            } else {
+                self.document.insert("$and", vec![clause]);
+            }
+        }
 
         self
     }
-}
 
-impl<T> FilterBuilder<T> {
     fn op<KP, V>(mut self, kp: KP, value: V, op: &'static str) -> Self
     where
         KP: KeyPathNonInitialNodeLike<Origin = T, Current = V>,
     {
         let serializer = kp.serializer();
+        let keypath = crate::kp::render(&kp);
 
         let bson = match serializer(&value) {
             Ok(bson) => bson,
             Err(e) => {
-                self.error = Some(e);
+                self.errors.push(Error::serialization(Some(keypath), op, e));
                 return self;
             }
         };
 
-        let keypath = crate::kp::render(&kp);
-
-        if self.document.get_document(&keypath).is_err() {
-            self.document.insert(&keypath, bson::Document::new());
-        }
+        self.insert_operators(keypath, bson::doc! { op: bson })
+    }
+}
 
-        let eq = self.document.get_document_mut(&keypath).unwrap();
-        eq.insert(op, bson);
+// Elements are serialized through the array's own serializer so that a
+// `serialize_with` on the field applies to each of them.
+pub(crate) fn serialize_elements<KP, V>(
+    kp: &KP,
+    values: &Vec<V>,
+) -> Result<Vec<bson::Bson>, bson::ser::Error>
+where
+    KP: KeyPathNonInitialNodeLike<Current = Vec<V>>,
+{
+    let serializer = kp.serializer();
 
-        self
+    match serializer(values)? {
+        bson::Bson::Array(elements) => Ok(elements),
+        other => Err(serde::ser::Error::custom(format!(
+            "expected {} to serialize to an array, got {:?}",
+            crate::kp::render(kp),
+            other.element_type()
+        ))),
     }
 }
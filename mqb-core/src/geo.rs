@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+pub type Position = [f64; 2];
+
+pub trait Geometry: Serialize {}
+
+pub trait Area: Geometry {}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub struct Point {
+    pub coordinates: Position,
+}
+
+impl Point {
+    pub fn new(longitude: f64, latitude: f64) -> Self {
+        Self {
+            coordinates: [longitude, latitude],
+        }
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.coordinates[0]
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.coordinates[1]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub struct LineString {
+    pub coordinates: Vec<Position>,
+}
+
+impl LineString {
+    pub fn new(coordinates: Vec<Position>) -> Self {
+        Self { coordinates }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub struct Polygon {
+    pub coordinates: Vec<Vec<Position>>,
+}
+
+impl Polygon {
+    pub fn new(exterior: Vec<Position>) -> Self {
+        Self {
+            coordinates: vec![exterior],
+        }
+    }
+
+    pub fn with_holes(
+        exterior: Vec<Position>,
+        holes: impl IntoIterator<Item = Vec<Position>>,
+    ) -> Self {
+        let mut coordinates = vec![exterior];
+        coordinates.extend(holes);
+
+        Self { coordinates }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub struct MultiPolygon {
+    pub coordinates: Vec<Vec<Vec<Position>>>,
+}
+
+impl MultiPolygon {
+    pub fn new(polygons: impl IntoIterator<Item = Polygon>) -> Self {
+        Self {
+            coordinates: polygons
+                .into_iter()
+                .map(|polygon| polygon.coordinates)
+                .collect(),
+        }
+    }
+}
+
+impl Geometry for Point {}
+
+impl Geometry for LineString {}
+
+impl Geometry for Polygon {}
+
+impl Geometry for MultiPolygon {}
+
+impl Area for Polygon {}
+
+impl Area for MultiPolygon {}
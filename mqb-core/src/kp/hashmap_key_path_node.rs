@@ -1,11 +1,12 @@
-use std::{collections::HashMap, marker::PhantomData};
+use std::{borrow::Cow, collections::HashMap, marker::PhantomData};
 
 use super::{
     KeyPathNodeLike, KeyPathNonInitialNodeLike, KeyPathable, SerializeFn,
+    UnsettableKeyPathNodeLike,
 };
 
 pub struct HashMapKeyPathNode<Parent: KeyPathNodeLike, V, UnderlyingType> {
-    key: &'static str,
+    key: Cow<'static, str>,
     parent: Parent,
     serializer: SerializeFn<HashMap<String, V>>,
     marker: std::marker::PhantomData<UnderlyingType>,
@@ -16,7 +17,7 @@ impl<Parent: KeyPathNodeLike, V, UnderlyingType> Clone
 {
     fn clone(&self) -> Self {
         Self {
-            key: self.key,
+            key: self.key.clone(),
             parent: self.parent.clone(),
             serializer: self.serializer,
             marker: PhantomData,
@@ -56,7 +57,7 @@ impl<Parent: KeyPathNodeLike, V, UnderlyingType> KeyPathNodeLike
 
     fn render_path(&self) -> String {
         if Parent::IS_ROOT {
-            self.key.to_owned()
+            self.key.to_string()
         } else {
             format!("{}.{}", self.parent.render_path(), self.key)
         }
@@ -70,7 +71,7 @@ impl<Parent: KeyPathNodeLike, V, UnderlyingType> KeyPathNonInitialNodeLike
     type UnderlyingType = HashMap<String, V>;
 
     fn instance(
-        key: &'static str,
+        key: Cow<'static, str>,
         serializer: SerializeFn<Self::Current>,
         parent: Self::ParentNodeTy,
     ) -> Self {
@@ -83,7 +84,7 @@ impl<Parent: KeyPathNodeLike, V, UnderlyingType> KeyPathNonInitialNodeLike
     }
 
     fn key(&self) -> String {
-        self.key.to_owned()
+        self.key.to_string()
     }
 
     fn parent(&self) -> &Self::ParentNodeTy {
@@ -124,7 +125,7 @@ where
     type UnderlyingType = V;
 
     fn instance(
-        _key: &'static str,
+        _key: Cow<'static, str>,
         _serializer: SerializeFn<Self::Current>,
         _parent: Self::ParentNodeTy,
     ) -> Self {
@@ -155,3 +156,10 @@ impl<Parent: KeyPathNonInitialNodeLike, V> Clone
         }
     }
 }
+
+impl<Parent: KeyPathNonInitialNodeLike, V> UnsettableKeyPathNodeLike
+    for HMKeyKeyPathNode<Parent, V>
+where
+    V: serde::Serialize,
+{
+}
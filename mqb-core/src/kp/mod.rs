@@ -1,9 +1,14 @@
+use std::borrow::Cow;
+
 mod terminal_key_path_node;
 pub use terminal_key_path_node::*;
 
 mod hashmap_key_path_node;
 pub use hashmap_key_path_node::*;
 
+mod positional_key_path_node;
+pub use positional_key_path_node::*;
+
 pub type SerializeFn<T> = fn(&T) -> bson::ser::Result<bson::Bson>;
 
 pub trait KeyPathable {
@@ -30,7 +35,7 @@ pub trait KeyPathNonInitialNodeLike: KeyPathNodeLike {
     type UnderlyingType;
 
     fn instance(
-        key: &'static str,
+        key: Cow<'static, str>,
         serializer: SerializeFn<Self::Current>,
         parent: Self::ParentNodeTy,
     ) -> Self;
@@ -50,6 +55,10 @@ pub trait KeyPathNonInitialNodeLike: KeyPathNodeLike {
 
 pub trait KeyPathInitialNodeLike: KeyPathNodeLike {}
 
+// Key paths which may be missing from a document, so removing them with
+// `$unset` still leaves a value that deserializes.
+pub trait UnsettableKeyPathNodeLike: KeyPathNonInitialNodeLike {}
+
 pub fn render<KP: KeyPathNodeLike>(node: &KP) -> String {
     node.render_path()
 }
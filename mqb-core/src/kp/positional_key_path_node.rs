@@ -0,0 +1,52 @@
+use std::marker::PhantomData;
+
+use super::KeyPathNodeLike;
+
+// Origin of key paths which go through a positional operator. The server
+// only accepts positional operators in updates, so only `UpdateBuilder`
+// takes these key paths.
+pub struct Positional<T>(PhantomData<T>);
+
+// Origins of the key paths an `UpdateBuilder<T>` accepts: `T` itself, or `T`
+// reached through positional operators on up to three nested arrays. A
+// blanket impl over nested `Positional`s would overlap with the one for `T`.
+pub trait UpdateOrigin<T> {}
+
+impl<T> UpdateOrigin<T> for T {}
+
+impl<T> UpdateOrigin<T> for Positional<T> {}
+
+impl<T> UpdateOrigin<T> for Positional<Positional<T>> {}
+
+impl<T> UpdateOrigin<T> for Positional<Positional<Positional<T>>> {}
+
+// Stands for an array whose elements are selected by a positional operator,
+// its path is the array's path.
+pub struct PositionalKeyPathNode<Parent: KeyPathNodeLike> {
+    array: Parent,
+}
+
+impl<Parent: KeyPathNodeLike> PositionalKeyPathNode<Parent> {
+    pub(crate) fn new(array: Parent) -> Self {
+        Self { array }
+    }
+}
+
+impl<Parent: KeyPathNodeLike> Clone for PositionalKeyPathNode<Parent> {
+    fn clone(&self) -> Self {
+        Self {
+            array: self.array.clone(),
+        }
+    }
+}
+
+impl<Parent: KeyPathNodeLike> KeyPathNodeLike
+    for PositionalKeyPathNode<Parent>
+{
+    type Origin = Positional<Parent::Origin>;
+    type Current = Parent::Current;
+
+    fn render_path(&self) -> String {
+        self.array.render_path()
+    }
+}
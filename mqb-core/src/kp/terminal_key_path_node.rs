@@ -1,14 +1,17 @@
-use std::marker::PhantomData;
+use std::{borrow::Cow, marker::PhantomData};
+
+use serde::Serialize;
 
 use super::{
-    KeyPathNodeLike, KeyPathNonInitialNodeLike, KeyPathable, SerializeFn,
+    KeyPathNodeLike, KeyPathNonInitialNodeLike, KeyPathable,
+    PositionalKeyPathNode, SerializeFn, UnsettableKeyPathNodeLike,
 };
 
 pub struct TerminalKeyPathNode<Parent, T, UnderlyingType = T>
 where
     Parent: KeyPathNodeLike,
 {
-    key: &'static str,
+    key: Cow<'static, str>,
     parent: Parent,
     serializer: SerializeFn<T>,
     marker: PhantomData<UnderlyingType>,
@@ -19,7 +22,7 @@ impl<Parent: KeyPathNodeLike, T, UnderlyingType> Clone
 {
     fn clone(&self) -> Self {
         Self {
-            key: self.key,
+            key: self.key.clone(),
             parent: self.parent.clone(),
             serializer: self.serializer,
             marker: self.marker,
@@ -35,7 +38,7 @@ impl<Parent: KeyPathNodeLike, T, UnderlyingType> KeyPathNodeLike
 
     fn render_path(&self) -> String {
         if Parent::IS_ROOT {
-            self.key.to_owned()
+            self.key.to_string()
         } else {
             format!("{}.{}", self.parent.render_path(), self.key)
         }
@@ -49,7 +52,7 @@ impl<Parent: KeyPathNodeLike, T, UnderlyingType> KeyPathNonInitialNodeLike
     type UnderlyingType = UnderlyingType;
 
     fn instance(
-        key: &'static str,
+        key: Cow<'static, str>,
         serializer: SerializeFn<Self::Current>,
         parent: Self::ParentNodeTy,
     ) -> Self {
@@ -62,7 +65,7 @@ impl<Parent: KeyPathNodeLike, T, UnderlyingType> KeyPathNonInitialNodeLike
     }
 
     fn key(&self) -> String {
-        self.key.to_owned()
+        self.key.to_string()
     }
 
     fn parent(&self) -> &Self::ParentNodeTy {
@@ -74,6 +77,76 @@ impl<Parent: KeyPathNodeLike, T, UnderlyingType> KeyPathNonInitialNodeLike
     }
 }
 
+impl<Parent: KeyPathNodeLike, T, UnderlyingType> UnsettableKeyPathNodeLike
+    for TerminalKeyPathNode<Parent, Option<T>, UnderlyingType>
+{
+}
+
+// Positional operators step into the elements of an array: the first one
+// matched by the query, every element, or the elements selected by the
+// array filter named `identifier`. The resulting key paths can only be used
+// in updates.
+impl<Parent, V, UnderlyingType>
+    TerminalKeyPathNode<Parent, Vec<V>, UnderlyingType>
+where
+    Parent: KeyPathNodeLike,
+    V: KeyPathable + Serialize,
+{
+    pub fn first_match(self) -> V::KeyPathNode<PositionalKeyPathNode<Self>, V>
+    where
+        V::KeyPathNode<PositionalKeyPathNode<Self>, V>:
+            KeyPathNonInitialNodeLike<
+                Current = V,
+                ParentNodeTy = PositionalKeyPathNode<Self>,
+            >,
+    {
+        self.element("$".into())
+    }
+
+    pub fn all(self) -> V::KeyPathNode<PositionalKeyPathNode<Self>, V>
+    where
+        V::KeyPathNode<PositionalKeyPathNode<Self>, V>:
+            KeyPathNonInitialNodeLike<
+                Current = V,
+                ParentNodeTy = PositionalKeyPathNode<Self>,
+            >,
+    {
+        self.element("$[]".into())
+    }
+
+    pub fn filtered(
+        self,
+        identifier: &str,
+    ) -> V::KeyPathNode<PositionalKeyPathNode<Self>, V>
+    where
+        V::KeyPathNode<PositionalKeyPathNode<Self>, V>:
+            KeyPathNonInitialNodeLike<
+                Current = V,
+                ParentNodeTy = PositionalKeyPathNode<Self>,
+            >,
+    {
+        self.element(format!("$[{}]", identifier).into())
+    }
+
+    fn element(
+        self,
+        key: Cow<'static, str>,
+    ) -> V::KeyPathNode<PositionalKeyPathNode<Self>, V>
+    where
+        V::KeyPathNode<PositionalKeyPathNode<Self>, V>:
+            KeyPathNonInitialNodeLike<
+                Current = V,
+                ParentNodeTy = PositionalKeyPathNode<Self>,
+            >,
+    {
+        V::KeyPathNode::<PositionalKeyPathNode<Self>, V>::instance(
+            key,
+            bson::to_bson,
+            PositionalKeyPathNode::new(self),
+        )
+    }
+}
+
 macro_rules! impl_key_pathable {
     ($($t:ty),*) => {
         $(
@@ -84,7 +157,23 @@ macro_rules! impl_key_pathable {
     };
 }
 
-impl_key_pathable!(String, i32, i64, f32, f64, bool, bson::oid::ObjectId);
+impl_key_pathable!(
+    String,
+    i32,
+    i64,
+    f32,
+    f64,
+    bool,
+    bson::oid::ObjectId,
+    bson::Decimal128
+);
+
+impl_key_pathable!(
+    crate::geo::Point,
+    crate::geo::LineString,
+    crate::geo::Polygon,
+    crate::geo::MultiPolygon
+);
 
 #[cfg(feature = "chrono")]
 impl_key_pathable!(chrono::DateTime<chrono::Utc>);
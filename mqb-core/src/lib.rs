@@ -1,7 +1,52 @@
+mod bit_mask;
+pub use bit_mask::*;
+
+mod bson_type;
+pub use bson_type::*;
+
+#[cfg(feature = "mongodb")]
+mod collection;
+#[cfg(feature = "mongodb")]
+pub use collection::*;
+
+mod element_filter;
+pub use element_filter::*;
+
+mod error;
+pub use error::*;
+
+mod eval;
+pub use eval::*;
+
+mod expr;
+pub use expr::*;
+
 mod filter;
 pub use filter::*;
 
+mod meta;
+pub use meta::*;
+
+mod numeric;
+pub use numeric::*;
+
+mod pattern;
+pub use pattern::*;
+
+mod projection;
+pub use projection::*;
+
+mod push;
+pub use push::*;
+
+mod schema;
+pub use schema::*;
+
+mod sort;
+pub use sort::*;
+
 mod update;
 pub use update::*;
 
+pub mod geo;
 pub mod kp;
@@ -0,0 +1,18 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Meta {
+    TextScore,
+}
+
+impl Meta {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Meta::TextScore => "textScore",
+        }
+    }
+}
+
+impl From<Meta> for bson::Bson {
+    fn from(meta: Meta) -> Self {
+        bson::Bson::Document(bson::doc! { "$meta": meta.keyword() })
+    }
+}
@@ -0,0 +1,26 @@
+// Field types accepted by the arithmetic update operators.
+pub trait Numeric {}
+
+macro_rules! impl_numeric {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {}
+        )*
+    };
+}
+
+impl_numeric!(
+    i8,
+    i16,
+    i32,
+    i64,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    f32,
+    f64,
+    bson::Decimal128
+);
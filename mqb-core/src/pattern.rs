@@ -0,0 +1,68 @@
+pub trait RegexTarget {}
+
+impl RegexTarget for String {}
+
+impl RegexTarget for Option<String> {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RegexOptions {
+    pub case_insensitive: bool,
+    pub multiline: bool,
+    pub extended: bool,
+    pub dot_all: bool,
+}
+
+impl RegexOptions {
+    pub fn case_insensitive() -> Self {
+        Self {
+            case_insensitive: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn render(&self) -> String {
+        [
+            (self.case_insensitive, 'i'),
+            (self.multiline, 'm'),
+            (self.extended, 'x'),
+            (self.dot_all, 's'),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, flag)| enabled.then_some(flag))
+        .collect()
+    }
+}
+
+pub fn escape_regex(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+
+    for c in literal.chars() {
+        if matches!(
+            c,
+            '\\' | '^'
+                | '$'
+                | '.'
+                | '|'
+                | '?'
+                | '*'
+                | '+'
+                | '('
+                | ')'
+                | '['
+                | ']'
+                | '{'
+                | '}'
+                | '#'
+                | ' '
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+pub fn prefix_regex(prefix: &str) -> String {
+    format!("^{}", escape_regex(prefix))
+}
@@ -0,0 +1,80 @@
+use crate::SortBuilder;
+
+pub enum PushSort<V> {
+    Ascending,
+    Descending,
+    By(SortBuilder<V>),
+}
+
+impl<V> From<SortBuilder<V>> for PushSort<V> {
+    fn from(sort: SortBuilder<V>) -> Self {
+        PushSort::By(sort)
+    }
+}
+
+impl<V> From<PushSort<V>> for bson::Bson {
+    fn from(sort: PushSort<V>) -> Self {
+        match sort {
+            PushSort::Ascending => 1.into(),
+            PushSort::Descending => (-1).into(),
+            PushSort::By(sort) => sort.build().into(),
+        }
+    }
+}
+
+// The `$slice`, `$sort` and `$position` modifiers of a `$push`. Sorting by
+// key paths of the element type only applies to arrays of subdocuments.
+pub struct PushModifiers<V> {
+    slice: Option<i64>,
+    sort: Option<PushSort<V>>,
+    position: Option<i64>,
+}
+
+impl<V> Default for PushModifiers<V> {
+    fn default() -> Self {
+        Self {
+            slice: None,
+            sort: None,
+            position: None,
+        }
+    }
+}
+
+impl<V> PushModifiers<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn slice(mut self, slice: i64) -> Self {
+        self.slice = Some(slice);
+        self
+    }
+
+    pub fn sort(mut self, sort: impl Into<PushSort<V>>) -> Self {
+        self.sort = Some(sort.into());
+        self
+    }
+
+    pub fn position(mut self, position: i64) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub(crate) fn build(self) -> bson::Document {
+        let mut modifiers = bson::Document::new();
+
+        if let Some(position) = self.position {
+            modifiers.insert("$position", position);
+        }
+
+        if let Some(sort) = self.sort {
+            modifiers.insert("$sort", sort);
+        }
+
+        if let Some(slice) = self.slice {
+            modifiers.insert("$slice", slice);
+        }
+
+        modifiers
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::BsonType;
+
+pub trait JsonSchema {
+    fn json_schema() -> bson::Document;
+}
+
+pub fn bson_type_schema(ty: BsonType) -> bson::Document {
+    bson::doc! { "bsonType": ty }
+}
+
+macro_rules! impl_json_schema {
+    ($($t:ty => $bson_type:expr),*) => {
+        $(
+            impl JsonSchema for $t {
+                fn json_schema() -> bson::Document {
+                    bson_type_schema($bson_type)
+                }
+            }
+        )*
+    };
+}
+
+impl_json_schema!(
+    String => BsonType::String,
+    i32 => BsonType::Int,
+    i64 => BsonType::Long,
+    f32 => BsonType::Double,
+    f64 => BsonType::Double,
+    bool => BsonType::Bool,
+    bson::oid::ObjectId => BsonType::ObjectId,
+    bson::DateTime => BsonType::Date,
+    bson::Decimal128 => BsonType::Decimal
+);
+
+// Without a `serialize_with` these serialize through their own serde
+// implementations, which produce strings.
+#[cfg(feature = "chrono")]
+impl_json_schema!(chrono::DateTime<chrono::Utc> => BsonType::String);
+
+#[cfg(feature = "uuid")]
+impl_json_schema!(uuid::Uuid => BsonType::String);
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema() -> bson::Document {
+        nullable(T::json_schema())
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema() -> bson::Document {
+        bson::doc! {
+            "bsonType": BsonType::Array,
+            "items": T::json_schema(),
+        }
+    }
+}
+
+impl<V: JsonSchema> JsonSchema for HashMap<String, V> {
+    fn json_schema() -> bson::Document {
+        bson::doc! {
+            "bsonType": BsonType::Object,
+            "additionalProperties": V::json_schema(),
+        }
+    }
+}
+
+macro_rules! impl_geo_json_schema {
+    ($($t:ident),*) => {
+        $(
+            impl JsonSchema for crate::geo::$t {
+                fn json_schema() -> bson::Document {
+                    bson::doc! {
+                        "bsonType": BsonType::Object,
+                        "required": ["type", "coordinates"],
+                        "properties": {
+                            "type": { "enum": [stringify!($t)] },
+                            "coordinates": { "bsonType": BsonType::Array },
+                        },
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_geo_json_schema!(Point, LineString, Polygon, MultiPolygon);
+
+pub fn nullable(mut schema: bson::Document) -> bson::Document {
+    let null = bson::Bson::from(BsonType::Null);
+
+    match schema.get_mut("bsonType") {
+        Some(bson::Bson::Array(types)) if !types.contains(&null) => {
+            types.push(null);
+        }
+        Some(ty) if *ty != null && !matches!(ty, bson::Bson::Array(_)) => {
+            *ty = bson::Bson::Array(vec![ty.clone(), null]);
+        }
+        // A schema without `bsonType` already accepts null.
+        _ => {}
+    }
+
+    schema
+}
@@ -0,0 +1,43 @@
+use crate::{kp::KeyPathNonInitialNodeLike, Meta};
+
+#[derive(Default)]
+pub struct SortBuilder<T> {
+    document: bson::Document,
+    marker: std::marker::PhantomData<T>,
+}
+
+impl<T> SortBuilder<T> {
+    pub fn new() -> Self {
+        Self {
+            document: bson::Document::new(),
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn build(self) -> bson::Document {
+        self.document
+    }
+}
+
+impl<T> SortBuilder<T> {
+    pub fn asc<KP>(mut self, kp: KP) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T>,
+    {
+        self.document.insert(crate::kp::render(&kp), 1);
+        self
+    }
+
+    pub fn desc<KP>(mut self, kp: KP) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin = T>,
+    {
+        self.document.insert(crate::kp::render(&kp), -1);
+        self
+    }
+
+    pub fn meta(mut self, field: impl Into<String>, meta: Meta) -> Self {
+        self.document.insert(field.into(), meta);
+        self
+    }
+}
@@ -1,15 +1,20 @@
 use std::marker::PhantomData;
 
-use crate::kp::KeyPathNonInitialNodeLike;
+use crate::{
+    filter::serialize_elements,
+    kp::{KeyPathNonInitialNodeLike, UnsettableKeyPathNodeLike, UpdateOrigin},
+    ElementFilter, Error, Errors, FilterBuilder, Numeric, PushModifiers,
+};
 use bson::doc;
-use num_traits::PrimInt;
 use serde::Serialize;
 
 #[derive(Default)]
 pub struct UpdateBuilder<T> {
     document: bson::Document,
     marker: PhantomData<T>,
-    error: Option<bson::ser::Error>,
+    errors: Vec<Error>,
+    // The identifier, the array it filters and the filter itself.
+    array_filters: Vec<(String, String, bson::Document)>,
 }
 
 impl<T> UpdateBuilder<T> {
@@ -17,113 +22,699 @@ impl<T> UpdateBuilder<T> {
         Self {
             document: bson::Document::new(),
             marker: PhantomData,
-            error: None,
+            errors: Vec::new(),
+            array_filters: Vec::new(),
         }
     }
 
-    pub fn try_build(self) -> Result<bson::Document, bson::ser::Error> {
-        if let Some(error) = self.error {
-            Err(error)
+    // Updates which use `filtered` key paths need their array filters sent
+    // along, so they have to be built with `try_build_with_array_filters`.
+    pub fn try_build(self) -> Result<bson::Document, Errors> {
+        let (document, array_filters) = self.try_build_with_array_filters()?;
+
+        if !array_filters.is_empty() {
+            return Err(Errors(vec![Error::invalid(
+                None,
+                "arrayFilters",
+                "the update has array filters, build it with \
+                 try_build_with_array_filters",
+            )]));
+        }
+
+        Ok(document)
+    }
+
+    pub fn try_build_with_array_filters(
+        mut self,
+    ) -> Result<(bson::Document, Vec<bson::Document>), Errors> {
+        self.check_conflicts();
+        self.check_array_filters();
+
+        let array_filters = self
+            .array_filters
+            .into_iter()
+            .map(|(_, _, filter)| filter)
+            .collect();
+
+        Errors::check(self.errors, (self.document, array_filters))
+    }
+
+    // Selects the elements updated through `filtered(identifier)` key paths
+    // on the same array.
+    pub fn array_filter<KP, V>(
+        self,
+        kp: KP,
+        identifier: &str,
+        element: impl FnOnce(FilterBuilder<V>) -> FilterBuilder<V>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+    {
+        let array_path = crate::kp::render(&kp);
+
+        self.insert_array_filter(array_path, identifier, |identifier| {
+            let document = element(FilterBuilder::new()).try_build_nested()?;
+            Ok((document.is_empty(), prefix_filter(document, identifier)))
+        })
+    }
+
+    // Array filters with conditions on the elements themselves, for arrays
+    // of scalars.
+    pub fn array_filter_value<KP, V>(
+        self,
+        kp: KP,
+        identifier: &str,
+        element: impl FnOnce(ElementFilter<V>) -> ElementFilter<V>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+    {
+        let array_path = crate::kp::render(&kp);
+        let serializer = kp.serializer();
+
+        self.insert_array_filter(array_path, identifier, |identifier| {
+            let document =
+                element(ElementFilter::new(serializer)).try_build()?;
+            Ok((document.is_empty(), doc! { identifier: document }))
+        })
+    }
+
+    // `build` returns whether the element conditions are empty along with
+    // the filter naming the identifier.
+    fn insert_array_filter(
+        mut self,
+        array_path: String,
+        identifier: &str,
+        build: impl FnOnce(&str) -> Result<(bool, bson::Document), Errors>,
+    ) -> Self {
+        if !is_identifier(identifier) {
+            self.errors.push(Error::invalid(
+                Some(identifier.to_owned()),
+                "arrayFilters",
+                "identifiers must start with a lowercase letter and only \
+                 contain letters and digits",
+            ));
+            return self;
+        }
+
+        if self
+            .array_filters
+            .iter()
+            .any(|(other, _, _)| other == identifier)
+        {
+            self.errors.push(Error::duplicate(
+                Some(identifier.to_owned()),
+                "arrayFilters",
+            ));
+            return self;
+        }
+
+        let filter = match build(identifier) {
+            Ok((false, filter)) => filter,
+            Ok((true, _)) => {
+                self.errors.push(Error::invalid(
+                    Some(identifier.to_owned()),
+                    "arrayFilters",
+                    "the filter is empty",
+                ));
+                return self;
+            }
+            Err(errors) => {
+                self.errors
+                    .extend(errors.into_iter().map(|e| e.within(identifier)));
+                return self;
+            }
+        };
+
+        self.array_filters
+            .push((identifier.to_owned(), array_path, filter));
+
+        self
+    }
+
+    fn field_op<KP, V>(mut self, kp: KP, value: V, op: &'static str) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin: UpdateOrigin<T>, Current = V>,
+    {
+        let serializer = kp.serializer();
+        let path = crate::kp::render(&kp);
+
+        let bson = match serializer(&value) {
+            Ok(bson) => bson,
+            Err(e) => {
+                self.errors.push(Error::serialization(Some(path), op, e));
+                return self;
+            }
+        };
+
+        self.single_op(path, bson, op)
+    }
+
+    // Values pushed to the same path accumulate under `$each`. Modifiers
+    // apply to the whole `$each`, so they can't be merged with other pushes.
+    fn each_op(
+        mut self,
+        path: String,
+        values: Vec<bson::Bson>,
+        modifiers: bson::Document,
+        op: &str,
+    ) -> Self {
+        if self.document.get_document(op).is_err() {
+            self.document.insert(op, bson::Document::new());
+        }
+
+        let each_op = self.document.get_document_mut(op).unwrap();
+        if let Ok(current_value) = each_op.get_document_mut(&path) {
+            if !modifiers.is_empty() || current_value.len() > 1 {
+                self.errors.push(Error::duplicate(Some(path), op));
+                return self;
+            }
+
+            let current_value_array =
+                current_value.get_array_mut("$each").unwrap();
+            current_value_array.extend(values);
         } else {
-            Ok(self.document)
+            let mut value = doc! { "$each": values };
+            value.extend(modifiers);
+            each_op.insert(path, value);
+        }
+
+        self
+    }
+
+    // A second `$set`, `$inc` or `$pull` on the same path would replace the
+    // first one, so it is reported instead.
+    fn single_op(mut self, path: String, bson: bson::Bson, op: &str) -> Self {
+        if self.document.get_document(op).is_err() {
+            self.document.insert(op, bson::Document::new());
         }
+
+        let single_op = self.document.get_document_mut(op).unwrap();
+        if single_op.contains_key(&path) {
+            self.errors.push(Error::duplicate(Some(path), op));
+            return self;
+        }
+
+        single_op.insert(path, bson);
+
+        self
+    }
+
+    fn unset_path(mut self, path: String) -> Self {
+        if self.document.get_document("$unset").is_err() {
+            self.document.insert("$unset", bson::Document::new());
+        }
+
+        let unset = self.document.get_document_mut("$unset").unwrap();
+        unset.insert(path, "");
+
+        self
     }
+
+    // The server rejects an update where two operators touch the same field,
+    // or a field and one of its subfields.
+    fn check_conflicts(&mut self) {
+        let paths = self
+            .document
+            .iter()
+            .filter_map(|(op, fields)| Some((op, fields.as_document()?)))
+            .flat_map(|(op, fields)| {
+                fields.iter().flat_map(move |(path, value)| {
+                    // `$rename` also writes to its target path.
+                    let target = value.as_str().filter(|_| op == "$rename");
+
+                    std::iter::once((op, path.as_str()))
+                        .chain(target.map(|target| (op, target)))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (index, (op, path)) in paths.iter().enumerate() {
+            for (other_op, other_path) in &paths[..index] {
+                if overlaps(path, other_path) {
+                    self.errors
+                        .push(Error::conflict(path, op, other_path, other_op));
+                }
+            }
+        }
+    }
+}
+
+impl<T> UpdateBuilder<T> {
+    // Every `$[identifier]` needs exactly one array filter, declared for the
+    // array it sits under, and the server rejects filters which no path uses.
+    fn check_array_filters(&mut self) {
+        let mut used = Vec::new();
+
+        for (op, fields) in &self.document {
+            let Some(fields) = fields.as_document() else {
+                continue;
+            };
+
+            for (path, value) in fields {
+                let target = value.as_str().filter(|_| op == "$rename");
+
+                for path in std::iter::once(path.as_str()).chain(target) {
+                    for (array, identifier) in identifiers(path) {
+                        match self
+                            .array_filters
+                            .iter()
+                            .find(|(filtered, _, _)| filtered == identifier)
+                        {
+                            None => self.errors.push(Error::invalid(
+                                Some(path.to_owned()),
+                                op,
+                                format!(
+                                    "no array filter for identifier '{}'",
+                                    identifier
+                                ),
+                            )),
+                            Some((_, array_path, _))
+                                if !same_array(array, array_path) =>
+                            {
+                                self.errors.push(Error::invalid(
+                                    Some(path.to_owned()),
+                                    op,
+                                    format!(
+                                        "identifier '{}' filters the \
+                                         elements of '{}'",
+                                        identifier, array_path
+                                    ),
+                                ))
+                            }
+                            Some(_) => {}
+                        }
+
+                        used.push(identifier.to_owned());
+                    }
+                }
+            }
+        }
+
+        for (identifier, _, _) in &self.array_filters {
+            if !used.contains(identifier) {
+                self.errors.push(Error::invalid(
+                    Some(identifier.clone()),
+                    "arrayFilters",
+                    "the identifier is not used by the update",
+                ));
+            }
+        }
+    }
+}
+
+// Yields each `$[identifier]` in the path with the path of the array it
+// selects elements from.
+fn identifiers(path: &str) -> impl Iterator<Item = (&str, &str)> {
+    path.split('.')
+        .scan(0_usize, |start, segment| {
+            let array = &path[..start.saturating_sub(1)];
+            *start += segment.len() + 1;
+            Some((array, segment))
+        })
+        .filter_map(|(array, segment)| Some((array, identifier(segment)?)))
+}
+
+// The array path may go through other positional segments, which select
+// elements of the same arrays whichever operator they use.
+fn same_array(path: &str, other: &str) -> bool {
+    path.split('.').count() == other.split('.').count()
+        && path
+            .split('.')
+            .zip(other.split('.'))
+            .all(|(segment, other)| {
+                segment == other
+                    || is_positional(segment) && is_positional(other)
+            })
+}
+
+fn is_identifier(identifier: &str) -> bool {
+    let mut chars = identifier.chars();
+
+    chars.next().is_some_and(|first| first.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric())
+}
+
+// Array filters name the element through the identifier, so the element
+// filter's paths are prefixed with it.
+fn prefix_filter(filter: bson::Document, identifier: &str) -> bson::Document {
+    filter
+        .into_iter()
+        .map(|(key, value)| match (key.as_str(), value) {
+            ("$and" | "$or" | "$nor", bson::Bson::Array(clauses)) => {
+                let clauses = clauses
+                    .into_iter()
+                    .map(|clause| match clause {
+                        bson::Bson::Document(clause) => {
+                            prefix_filter(clause, identifier).into()
+                        }
+                        clause => clause,
+                    })
+                    .collect::<Vec<_>>();
+
+                (key, clauses.into())
+            }
+            (_, value) if key.starts_with('$') => (key, value),
+            (_, value) => (format!("{}.{}", identifier, key), value),
+        })
+        .collect()
+}
+
+fn serialize_values<V: Serialize>(
+    values: impl IntoIterator<Item = impl Into<V>>,
+) -> Result<Vec<bson::Bson>, bson::ser::Error> {
+    values
+        .into_iter()
+        .map(|value| bson::to_bson(&value.into()))
+        .collect()
+}
+
+// Types a field of type `S` can be renamed into: the same type, or an
+// optional version of it.
+pub trait RenameTarget<S> {}
+
+impl<S> RenameTarget<S> for S {}
+
+impl<S> RenameTarget<S> for Option<S> {}
+
+// Positional segments (`$`, `$[]` and `$[identifier]`) may select any
+// element, so they overlap with other segments at the same depth. Two
+// different identifiers only conflict when an element matches both filters,
+// which the server reports once it applies the update.
+pub(crate) fn overlaps(path: &str, other: &str) -> bool {
+    path.split('.')
+        .zip(other.split('.'))
+        .all(|(segment, other)| {
+            segment == other
+                || (is_positional(segment) || is_positional(other))
+                    && !(identifier(segment).is_some()
+                        && identifier(other).is_some())
+        })
+}
+
+fn is_positional(segment: &str) -> bool {
+    segment == "$" || segment.starts_with("$[") && segment.ends_with(']')
+}
+
+fn identifier(segment: &str) -> Option<&str> {
+    segment
+        .strip_prefix("$[")?
+        .strip_suffix(']')
+        .filter(|identifier| !identifier.is_empty())
 }
 
 impl<T> UpdateBuilder<T> {
     pub fn set<KP, V>(mut self, kp: KP, value: impl Into<V>) -> Self
     where
-        KP: KeyPathNonInitialNodeLike<Origin = T, Current = V>,
+        KP: KeyPathNonInitialNodeLike<Origin: UpdateOrigin<T>, Current = V>,
     {
         let serializer = kp.serializer();
+        let path = crate::kp::render(&kp);
 
         let bson = match serializer(&value.into()) {
             Ok(bson) => bson,
             Err(e) => {
-                self.error = Some(e);
+                self.errors
+                    .push(Error::serialization(Some(path), "$set", e));
                 return self;
             }
         };
 
+        self.single_op(path, bson, "$set")
+    }
+
+    // Like `set`, but a value which serializes to null removes the field
+    // instead of storing an explicit null. Only fields which may be missing
+    // can be removed.
+    pub fn set_or_unset<KP, V>(mut self, kp: KP, value: impl Into<V>) -> Self
+    where
+        KP: UnsettableKeyPathNodeLike<Origin: UpdateOrigin<T>, Current = V>,
+    {
+        let serializer = kp.serializer();
         let path = crate::kp::render(&kp);
 
-        if self.document.get_document("$set").is_err() {
-            self.document.insert("$set", bson::Document::new());
+        match serializer(&value.into()) {
+            Ok(bson::Bson::Null) => self.unset_path(path),
+            Ok(bson) => self.single_op(path, bson, "$set"),
+            Err(e) => {
+                self.errors
+                    .push(Error::serialization(Some(path), "$set", e));
+                self
+            }
         }
-
-        let set = self.document.get_document_mut("$set").unwrap();
-        set.insert(path, bson);
-
-        self
     }
 
     pub fn set_on_insert<KP, V>(mut self, kp: KP, value: impl Into<V>) -> Self
     where
-        KP: KeyPathNonInitialNodeLike<Origin = T, Current = V>,
+        KP: KeyPathNonInitialNodeLike<Origin: UpdateOrigin<T>, Current = V>,
     {
         let serializer = kp.serializer();
+        let path = crate::kp::render(&kp);
 
         let bson = match serializer(&value.into()) {
             Ok(bson) => bson,
             Err(e) => {
-                self.error = Some(e);
+                self.errors.push(Error::serialization(
+                    Some(path),
+                    "$setOnInsert",
+                    e,
+                ));
                 return self;
             }
         };
 
+        self.single_op(path, bson, "$setOnInsert")
+    }
+
+    pub fn unset<KP>(self, kp: KP) -> Self
+    where
+        KP: UnsettableKeyPathNodeLike<Origin: UpdateOrigin<T>>,
+    {
         let path = crate::kp::render(&kp);
+        self.unset_path(path)
+    }
 
-        if self.document.get_document("$setOnInsert").is_err() {
-            self.document.insert("$setOnInsert", bson::Document::new());
-        }
+    pub fn rename<From, To>(self, from: From, to: To) -> Self
+    where
+        From: KeyPathNonInitialNodeLike<Origin = T>,
+        To: KeyPathNonInitialNodeLike<
+            Origin = T,
+            Current: RenameTarget<From::Current>,
+        >,
+    {
+        let from = crate::kp::render(&from);
+        let to = crate::kp::render(&to);
 
-        let set_on_insert =
-            self.document.get_document_mut("$setOnInsert").unwrap();
-        set_on_insert.insert(path, bson);
+        self.single_op(from, to.into(), "$rename")
+    }
 
-        self
+    pub fn push<KP, V>(self, kp: KP, value: impl Into<V>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+        V: Serialize,
+    {
+        self.push_with(kp, [value], PushModifiers::new())
+    }
+
+    pub fn push_many<KP, V>(
+        self,
+        kp: KP,
+        values: impl IntoIterator<Item = impl Into<V>>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+        V: Serialize,
+    {
+        self.push_with(kp, values, PushModifiers::new())
     }
 
-    pub fn push<KP, V>(mut self, kp: KP, value: impl Into<V>) -> Self
+    pub fn push_with<KP, V>(
+        mut self,
+        kp: KP,
+        values: impl IntoIterator<Item = impl Into<V>>,
+        modifiers: PushModifiers<V>,
+    ) -> Self
     where
-        KP: KeyPathNonInitialNodeLike<Origin = T, Current = Vec<V>>,
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
         V: Serialize,
     {
         let path = crate::kp::render(&kp);
-        let bson = match bson::to_bson(&value.into()) {
+        let bson_values = match serialize_values(values) {
+            Ok(bson) => bson,
+            Err(e) => {
+                self.errors
+                    .push(Error::serialization(Some(path), "$push", e));
+                return self;
+            }
+        };
+
+        self.each_op(path, bson_values, modifiers.build(), "$push")
+    }
+
+    pub fn pull<KP, V>(mut self, kp: KP, value: impl Into<V>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+    {
+        let path = crate::kp::render(&kp);
+
+        // The value has to equal the stored elements, so it goes through the
+        // field's serializer as a single element array.
+        let bson = match serialize_elements(&kp, &vec![value.into()]) {
+            Ok(elements) => match elements.into_iter().next() {
+                Some(bson) => bson,
+                None => {
+                    self.errors.push(Error::invalid(
+                        Some(path),
+                        "$pull",
+                        "the value serialized to an empty array",
+                    ));
+                    return self;
+                }
+            },
+            Err(e) => {
+                self.errors
+                    .push(Error::serialization(Some(path), "$pull", e));
+                return self;
+            }
+        };
+
+        self.single_op(path, bson, "$pull")
+    }
+
+    pub fn pull_where<KP, V>(
+        mut self,
+        kp: KP,
+        element: impl FnOnce(FilterBuilder<V>) -> FilterBuilder<V>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+    {
+        let path = crate::kp::render(&kp);
+        let document = match element(FilterBuilder::new()).try_build_nested() {
+            Ok(document) => document,
+            Err(errors) => {
+                self.errors
+                    .extend(errors.into_iter().map(|e| e.within(&path)));
+                return self;
+            }
+        };
+
+        self.single_op(path, document.into(), "$pull")
+    }
+
+    // `$pull` with conditions on the elements themselves, for arrays of
+    // scalars.
+    pub fn pull_where_value<KP, V>(
+        mut self,
+        kp: KP,
+        element: impl FnOnce(ElementFilter<V>) -> ElementFilter<V>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+    {
+        let path = crate::kp::render(&kp);
+        let document =
+            match element(ElementFilter::new(kp.serializer())).try_build() {
+                Ok(document) => document,
+                Err(errors) => {
+                    self.errors
+                        .extend(errors.into_iter().map(|e| e.within(&path)));
+                    return self;
+                }
+            };
+
+        self.single_op(path, document.into(), "$pull")
+    }
+
+    pub fn pull_all<KP, V>(
+        mut self,
+        kp: KP,
+        values: impl IntoIterator<Item = impl Into<V>>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+    {
+        let path = crate::kp::render(&kp);
+        let values = values.into_iter().map(Into::into).collect::<Vec<_>>();
+        let bson_values = match serialize_elements(&kp, &values) {
             Ok(bson) => bson,
             Err(e) => {
-                self.error = Some(e);
+                self.errors.push(Error::serialization(
+                    Some(path),
+                    "$pullAll",
+                    e,
+                ));
                 return self;
             }
         };
 
-        if self.document.get_document("$push").is_err() {
-            self.document.insert("$push", bson::Document::new());
+        if self.document.get_document("$pullAll").is_err() {
+            self.document.insert("$pullAll", bson::Document::new());
         }
 
-        let push = self.document.get_document_mut("$push").unwrap();
-        if push.contains_key(&path) {
-            let current_value = push.get_document_mut(&path).unwrap();
-            let current_value_array =
-                current_value.get_array_mut("$each").unwrap();
-            current_value_array.push(bson);
+        let pull_all = self.document.get_document_mut("$pullAll").unwrap();
+        if let Ok(current_values) = pull_all.get_array_mut(&path) {
+            current_values.extend(bson_values);
         } else {
-            push.insert(
-                path,
-                doc! {
-                    "$each": [bson]
-                },
-            );
+            pull_all.insert(path, bson_values);
         }
 
         self
     }
 
+    pub fn pop_first<KP, V>(self, kp: KP) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+    {
+        let path = crate::kp::render(&kp);
+        self.single_op(path, (-1).into(), "$pop")
+    }
+
+    pub fn pop_last<KP, V>(self, kp: KP) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
+            Current = Vec<V>,
+        >,
+    {
+        let path = crate::kp::render(&kp);
+        self.single_op(path, 1.into(), "$pop")
+    }
+
     pub fn current_date<KP>(mut self, kp: KP) -> Self
     where
         KP: KeyPathNonInitialNodeLike<
-            Origin = T,
+            Origin: UpdateOrigin<T>,
             UnderlyingType = bson::DateTime,
         >,
     {
@@ -140,58 +731,74 @@ impl<T> UpdateBuilder<T> {
         self
     }
 
-    pub fn add_to_set<KP, V>(mut self, kp: KP, value: impl Into<V>) -> Self
+    pub fn add_to_set<KP, V>(self, kp: KP, value: impl Into<V>) -> Self
     where
         KP: KeyPathNonInitialNodeLike<
-            Origin = T,
+            Origin: UpdateOrigin<T>,
+            Current: IntoIterator<Item = V>,
+        >,
+        V: Serialize,
+    {
+        self.add_to_set_many(kp, [value])
+    }
+
+    pub fn add_to_set_many<KP, V>(
+        mut self,
+        kp: KP,
+        values: impl IntoIterator<Item = impl Into<V>>,
+    ) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<
+            Origin: UpdateOrigin<T>,
             Current: IntoIterator<Item = V>,
         >,
         V: Serialize,
     {
         let path = crate::kp::render(&kp);
-        let bson = match bson::to_bson(&value.into()) {
+        let bson_values = match serialize_values(values) {
             Ok(bson) => bson,
             Err(e) => {
-                self.error = Some(e);
+                self.errors.push(Error::serialization(
+                    Some(path),
+                    "$addToSet",
+                    e,
+                ));
                 return self;
             }
         };
 
-        if self.document.get_document("$addToSet").is_err() {
-            self.document.insert("$addToSet", bson::Document::new());
-        }
-
-        let add_to_set = self.document.get_document_mut("$addToSet").unwrap();
-        if add_to_set.contains_key(&path) {
-            let current_value = add_to_set.get_document_mut(&path).unwrap();
-            let current_value_array =
-                current_value.get_array_mut("$each").unwrap();
-            current_value_array.push(bson);
-        } else {
-            add_to_set.insert(
-                path,
-                doc! {
-                    "$each": [bson]
-                },
-            );
-        }
-
-        self
+        self.each_op(path, bson_values, bson::Document::new(), "$addToSet")
     }
 
-    pub fn inc<KP>(mut self, kp: KP, amount: i64) -> Self
+    pub fn inc<KP, V>(self, kp: KP, amount: impl Into<V>) -> Self
     where
-        KP: KeyPathNonInitialNodeLike<Origin = T, Current: PrimInt>,
+        KP: KeyPathNonInitialNodeLike<Origin: UpdateOrigin<T>, Current = V>,
+        V: Numeric,
     {
-        let path = crate::kp::render(&kp);
+        self.field_op(kp, amount.into(), "$inc")
+    }
 
-        if self.document.get_document("$inc").is_err() {
-            self.document.insert("$inc", bson::Document::new());
-        }
+    pub fn mul<KP, V>(self, kp: KP, factor: impl Into<V>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin: UpdateOrigin<T>, Current = V>,
+        V: Numeric,
+    {
+        self.field_op(kp, factor.into(), "$mul")
+    }
 
-        let inc = self.document.get_document_mut("$inc").unwrap();
-        inc.insert(path, amount);
+    pub fn min<KP, V>(self, kp: KP, value: impl Into<V>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin: UpdateOrigin<T>, Current = V>,
+        V: PartialOrd,
+    {
+        self.field_op(kp, value.into(), "$min")
+    }
 
-        self
+    pub fn max<KP, V>(self, kp: KP, value: impl Into<V>) -> Self
+    where
+        KP: KeyPathNonInitialNodeLike<Origin: UpdateOrigin<T>, Current = V>,
+        V: PartialOrd,
+    {
+        self.field_op(kp, value.into(), "$max")
     }
 }
@@ -49,6 +49,23 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
 
     let serializers = get_serializers(fields);
 
+    let skipped_fields = get_skipped_fields(fields);
+    let (schema_field_name_str, json_schemas): (Vec<_>, Vec<_>) =
+        serde_field_name_str
+            .iter()
+            .zip(get_json_schemas(fields))
+            .zip(&skipped_fields)
+            .filter_map(|(field, skipped)| (!skipped).then_some(field))
+            .unzip();
+    let required_field_name_str = serde_field_name_str
+        .iter()
+        .zip(get_required_fields(fields))
+        .zip(&skipped_fields)
+        .filter_map(|((name, required), skipped)| {
+            (required && !skipped).then_some(name)
+        })
+        .collect::<Vec<_>>();
+
     let struct_name = &input.ident;
 
     let key_path_node_name = syn::Ident::new(
@@ -63,7 +80,7 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
 
     quote! {
         pub struct #key_path_node_name<Parent, UnderlyingType = #struct_name> where Parent: mqb_core::kp::KeyPathNodeLike {
-            key: &'static str,
+            key: std::borrow::Cow<'static, str>,
             parent: Parent,
             serializer: mqb_core::kp::SerializeFn<#struct_name>,
             marker: std::marker::PhantomData<UnderlyingType>,
@@ -72,7 +89,7 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
         impl<Parent: mqb_core::kp::KeyPathNodeLike, UnderlyingType> Clone for #key_path_node_name<Parent, UnderlyingType> {
             fn clone(&self) -> Self {
                 #key_path_node_name {
-                    key: self.key,
+                    key: self.key.clone(),
                     parent: self.parent.clone(),
                     serializer: self.serializer,
                     marker: std::marker::PhantomData,
@@ -86,7 +103,7 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
 
             fn render_path(&self) -> String {
                 if Parent::IS_ROOT {
-                    self.key.to_owned()
+                    self.key.to_string()
                 } else {
                     format!("{}.{}", self.parent.render_path(), self.key)
                 }
@@ -98,7 +115,7 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
             type ParentNodeTy = Parent;
             type UnderlyingType = UnderlyingType;
 
-            fn instance(key: &'static str, serializer: mqb_core::kp::SerializeFn<Self::Current>, parent: Self::ParentNodeTy) -> Self {
+            fn instance(key: std::borrow::Cow<'static, str>, serializer: mqb_core::kp::SerializeFn<Self::Current>, parent: Self::ParentNodeTy) -> Self {
                 #key_path_node_name {
                     key,
                     parent,
@@ -108,7 +125,7 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
             }
 
             fn key(&self) -> String {
-                self.key.to_owned()
+                self.key.to_string()
             }
 
             fn parent(&self) -> &Self::ParentNodeTy {
@@ -150,11 +167,30 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
             }
         }
 
+        impl mqb_core::JsonSchema for #struct_name {
+            fn json_schema() -> bson::Document {
+                let mut schema = bson::doc! { "bsonType": mqb_core::BsonType::Object };
+
+                let required: Vec<&str> = vec![#(#required_field_name_str),*];
+                if !required.is_empty() {
+                    schema.insert("required", required);
+                }
+
+                let mut properties = bson::Document::new();
+                #(
+                    properties.insert(#schema_field_name_str, #json_schemas);
+                )*
+                schema.insert("properties", properties);
+
+                schema
+            }
+        }
+
         impl<Parent: mqb_core::kp::KeyPathNodeLike> #key_path_node_name<Parent, #struct_name> {
             #(
                 pub fn #field_name(self) -> <#field_type as mqb_core::kp::KeyPathable>::KeyPathNode<Self, #underlying_types> {
                     use mqb_core::kp::*;
-                    <#field_type as mqb_core::kp::KeyPathable>::KeyPathNode::<Self, #underlying_types>::instance(#serde_field_name_str, #serializers, self)
+                    <#field_type as mqb_core::kp::KeyPathable>::KeyPathNode::<Self, #underlying_types>::instance(#serde_field_name_str.into(), #serializers, self)
                 }
             )*
         }
@@ -163,7 +199,7 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
             #(
                 pub fn #field_name(self) -> <#field_type as mqb_core::kp::KeyPathable>::KeyPathNode<Self, #underlying_types> {
                     use mqb_core::kp::*;
-                    <#field_type as mqb_core::kp::KeyPathable>::KeyPathNode::<Self, #underlying_types>::instance(#serde_field_name_str, #serializers, self)
+                    <#field_type as mqb_core::kp::KeyPathable>::KeyPathNode::<Self, #underlying_types>::instance(#serde_field_name_str.into(), #serializers, self)
                 }
             )*
         }
@@ -171,6 +207,8 @@ fn expand_derive_keypathable(input: DeriveInput) -> TokenStream {
 }
 
 fn derive_leaf_keypathable(input: DeriveInput) -> TokenStream {
+    let json_schema = get_leaf_json_schema(&input);
+
     let item_name = match input.data {
         Data::Struct(_) => input.ident,
         Data::Enum(_) => input.ident,
@@ -181,5 +219,11 @@ fn derive_leaf_keypathable(input: DeriveInput) -> TokenStream {
         impl mqb_core::kp::KeyPathable for #item_name {
             type KeyPathNode<Parent: mqb_core::kp::KeyPathNodeLike, UnderlyingType> = mqb_core::kp::TerminalKeyPathNode<Parent, UnderlyingType>;
         }
+
+        impl mqb_core::JsonSchema for #item_name {
+            fn json_schema() -> bson::Document {
+                #json_schema
+            }
+        }
     }
 }
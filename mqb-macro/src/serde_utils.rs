@@ -200,3 +200,189 @@ pub fn get_serialize_with_paths(
         })
         .collect()
 }
+
+pub fn get_json_schemas(
+    fields: &Punctuated<syn::Field, Comma>,
+) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .zip(get_serialize_with_paths(fields))
+        .map(|(field, serialize_fn)| {
+            let ty = &field.ty;
+
+            let schema = match serialize_fn {
+                None => {
+                    return quote! {
+                        <#ty as mqb_core::JsonSchema>::json_schema()
+                    }
+                }
+                Some(serialize_with) => {
+                    match get_serialize_with_bson_type(&serialize_with) {
+                        Some(bson_type) => {
+                            let bson_type = syn::Ident::new(
+                                bson_type,
+                                proc_macro2::Span::call_site(),
+                            );
+                            quote! {
+                                mqb_core::bson_type_schema(mqb_core::BsonType::#bson_type)
+                            }
+                        }
+                        // The serializer is opaque, so any BSON type is
+                        // accepted for the field.
+                        None => quote! { bson::Document::new() },
+                    }
+                }
+            };
+
+            if is_option(ty) {
+                quote! { mqb_core::nullable(#schema) }
+            } else {
+                schema
+            }
+        })
+        .collect()
+}
+
+pub fn get_required_fields(
+    fields: &Punctuated<syn::Field, Comma>,
+) -> Vec<bool> {
+    fields
+        .iter()
+        .map(|field| {
+            let mut skip_serializing_if = false;
+
+            for attr in &field.attrs {
+                if attr.path().is_ident("serde")
+                    && attr.path().segments.len() == 1
+                {
+                    _ = attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("skip_serializing_if") {
+                            skip_serializing_if = true;
+                        }
+
+                        if meta.input.peek(syn::Token![=]) {
+                            meta.value()?.parse::<syn::Expr>()?;
+                        }
+
+                        Ok(())
+                    });
+                }
+            }
+
+            !skip_serializing_if && !is_option(&field.ty)
+        })
+        .collect()
+}
+
+// Fields which never reach the serialized document.
+pub fn get_skipped_fields(fields: &Punctuated<syn::Field, Comma>) -> Vec<bool> {
+    fields
+        .iter()
+        .map(|field| {
+            let mut skipped = false;
+
+            for attr in &field.attrs {
+                if attr.path().is_ident("serde")
+                    && attr.path().segments.len() == 1
+                {
+                    _ = attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("skip")
+                            || meta.path.is_ident("skip_serializing")
+                        {
+                            skipped = true;
+                        }
+
+                        if meta.input.peek(syn::Token![=]) {
+                            meta.value()?.parse::<syn::Expr>()?;
+                        }
+
+                        Ok(())
+                    });
+                }
+            }
+
+            skipped
+        })
+        .collect()
+}
+
+pub fn get_leaf_json_schema(input: &syn::DeriveInput) -> TokenStream {
+    let mut integer_repr = false;
+
+    for attr in &input.attrs {
+        if attr.path().is_ident("repr") {
+            _ = attr.parse_nested_meta(|meta| {
+                integer_repr |= meta.path.get_ident().is_some_and(|ident| {
+                    matches!(
+                        ident.to_string().as_str(),
+                        "u8" | "u16"
+                            | "u32"
+                            | "u64"
+                            | "usize"
+                            | "i8"
+                            | "i16"
+                            | "i32"
+                            | "i64"
+                            | "isize"
+                    )
+                });
+
+                Ok(())
+            });
+        }
+    }
+
+    let unit_enum = match &input.data {
+        syn::Data::Enum(data) => data
+            .variants
+            .iter()
+            .all(|variant| matches!(variant.fields, syn::Fields::Unit)),
+        _ => false,
+    };
+
+    // Field-less enums serialize as variant names, unless they go through
+    // serde_repr. An integer repr doesn't tell which, so any BSON type is
+    // accepted for those.
+    if !unit_enum || integer_repr {
+        return quote! { bson::Document::new() };
+    }
+
+    quote! {
+        mqb_core::bson_type_schema(mqb_core::BsonType::String)
+    }
+}
+
+fn get_serialize_with_bson_type(serialize_with: &str) -> Option<&'static str> {
+    let path = serialize_with
+        .trim_start_matches("bson::serde_helpers::")
+        .trim_start_matches("serialize_");
+
+    match path {
+        "chrono_datetime_as_bson_datetime"
+        | "rfc3339_string_as_bson_datetime" => Some("Date"),
+        "bson_datetime_as_rfc3339_string" | "object_id_as_hex_string" => {
+            Some("String")
+        }
+        "hex_string_as_object_id" => Some("ObjectId"),
+        "u32_as_f64" | "u64_as_f64" => Some("Double"),
+        "u32_as_i32" | "u64_as_i32" => Some("Int"),
+        "u32_as_i64" | "u64_as_i64" => Some("Long"),
+        "u32_as_timestamp" => Some("Timestamp"),
+        "uuid_1_as_binary"
+        | "uuid_1_as_java_legacy_binary"
+        | "uuid_1_as_python_legacy_binary"
+        | "uuid_1_as_c_sharp_legacy_binary" => Some("BinData"),
+        _ => None,
+    }
+}
+
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
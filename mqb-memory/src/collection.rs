@@ -0,0 +1,261 @@
+use std::{marker::PhantomData, sync::Mutex};
+
+use bson::{oid::ObjectId, Bson, Document};
+use mqb_core::{
+    kp::KeyPathableAsRoot, FilterBuilder, Matcher, UpdateBuilder, Updater,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::MemoryError;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UpdateResult {
+    pub matched_count: u64,
+    pub modified_count: u64,
+    pub upserted_id: Option<Bson>,
+}
+
+// Documents are kept in insertion order, which is also the natural order
+// returned by `find`. Like the server, `$near` queries return the nearest
+// documents first.
+pub struct MemoryCollection<T> {
+    documents: Mutex<Vec<Document>>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Default for MemoryCollection<T> {
+    fn default() -> Self {
+        Self {
+            documents: Mutex::new(Vec::new()),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> MemoryCollection<T>
+where
+    T: KeyPathableAsRoot + Serialize + DeserializeOwned,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn insert_one(&self, value: &T) -> Result<Bson, MemoryError> {
+        let document =
+            bson::to_document(value).map_err(MemoryError::Serialization)?;
+
+        self.insert_document(document)
+    }
+
+    pub fn insert_many<'a>(
+        &self,
+        values: impl IntoIterator<Item = &'a T>,
+    ) -> Result<Vec<Bson>, MemoryError>
+    where
+        T: 'a,
+    {
+        values
+            .into_iter()
+            .map(|value| self.insert_one(value))
+            .collect()
+    }
+
+    pub fn find(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<Vec<T>, MemoryError> {
+        let matcher = Matcher::new(filter)?;
+        let documents = self.documents.lock().unwrap();
+
+        matches(&matcher, &documents)
+            .into_iter()
+            .map(|document| deserialize(document.clone()))
+            .collect()
+    }
+
+    pub fn find_one(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<Option<T>, MemoryError> {
+        let matcher = Matcher::new(filter)?;
+        let documents = self.documents.lock().unwrap();
+
+        matches(&matcher, &documents)
+            .first()
+            .map(|document| deserialize((*document).clone()))
+            .transpose()
+    }
+
+    pub fn count(&self, filter: FilterBuilder<T>) -> Result<u64, MemoryError> {
+        let matcher = Matcher::new(filter)?;
+        let documents = self.documents.lock().unwrap();
+
+        Ok(documents
+            .iter()
+            .filter(|document| matcher.matches_document(document))
+            .count() as u64)
+    }
+
+    pub fn update_one(
+        &self,
+        filter: FilterBuilder<T>,
+        update: UpdateBuilder<T>,
+    ) -> Result<UpdateResult, MemoryError> {
+        self.update(filter, update, false, false)
+    }
+
+    pub fn update_many(
+        &self,
+        filter: FilterBuilder<T>,
+        update: UpdateBuilder<T>,
+    ) -> Result<UpdateResult, MemoryError> {
+        self.update(filter, update, true, false)
+    }
+
+    pub fn upsert(
+        &self,
+        filter: FilterBuilder<T>,
+        update: UpdateBuilder<T>,
+    ) -> Result<UpdateResult, MemoryError> {
+        self.update(filter, update, false, true)
+    }
+
+    pub fn delete_one(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<u64, MemoryError> {
+        let matcher = Matcher::new(filter)?;
+        let mut documents = self.documents.lock().unwrap();
+
+        match documents
+            .iter()
+            .position(|document| matcher.matches_document(document))
+        {
+            Some(index) => {
+                documents.remove(index);
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    pub fn delete_many(
+        &self,
+        filter: FilterBuilder<T>,
+    ) -> Result<u64, MemoryError> {
+        let matcher = Matcher::new(filter)?;
+        let mut documents = self.documents.lock().unwrap();
+
+        let before = documents.len();
+        documents.retain(|document| !matcher.matches_document(document));
+
+        Ok((before - documents.len()) as u64)
+    }
+
+    fn insert_document(&self, document: Document) -> Result<Bson, MemoryError> {
+        let mut documents = self.documents.lock().unwrap();
+        insert_into::<T>(&mut documents, document)
+    }
+
+    fn update(
+        &self,
+        filter: FilterBuilder<T>,
+        update: UpdateBuilder<T>,
+        multi: bool,
+        upsert: bool,
+    ) -> Result<UpdateResult, MemoryError> {
+        let matcher = Matcher::new(filter)?;
+        let updater = Updater::new(update)?;
+        let mut result = UpdateResult::default();
+
+        // The lock is held until a possible upsert is inserted, so two
+        // upserts can't both miss and insert the same document.
+        let mut documents = self.documents.lock().unwrap();
+
+        // Every match is updated on a copy first so a failing document
+        // leaves the whole collection untouched.
+        let mut updated = Vec::new();
+        for (index, document) in documents.iter().enumerate() {
+            if !matcher.matches_document(document) {
+                continue;
+            }
+
+            let mut document = document.clone();
+            updater.apply_to_matched_document(&mut document, &matcher)?;
+            deserialize::<T>(document.clone())?;
+            updated.push((index, document));
+
+            if !multi {
+                break;
+            }
+        }
+
+        for (index, document) in updated {
+            result.matched_count += 1;
+            if documents[index] != document {
+                result.modified_count += 1;
+                documents[index] = document;
+            }
+        }
+
+        if result.matched_count == 0 && upsert {
+            let mut document = matcher.upsert_document();
+            updater.upsert(true).apply_to_document(&mut document)?;
+            result.upserted_id =
+                Some(insert_into::<T>(&mut documents, document)?);
+        }
+
+        Ok(result)
+    }
+}
+
+fn insert_into<T: DeserializeOwned>(
+    documents: &mut Vec<Document>,
+    mut document: Document,
+) -> Result<Bson, MemoryError> {
+    let id = document
+        .entry("_id".to_owned())
+        .or_insert_with(|| Bson::ObjectId(ObjectId::new()))
+        .clone();
+
+    if documents
+        .iter()
+        .any(|existing| existing.get("_id") == Some(&id))
+    {
+        return Err(MemoryError::DuplicateKey(id));
+    }
+
+    // Only documents which read back as `T` are accepted.
+    deserialize::<T>(document.clone())?;
+    documents.push(document);
+
+    Ok(id)
+}
+
+// Matching documents in the order `find` returns them.
+fn matches<'a, T>(
+    matcher: &Matcher<T>,
+    documents: &'a [Document],
+) -> Vec<&'a Document> {
+    let mut matches = documents
+        .iter()
+        .filter(|document| matcher.matches_document(document))
+        .collect::<Vec<_>>();
+
+    matcher.sort_nearest_first(&mut matches);
+    matches
+}
+
+fn deserialize<T: DeserializeOwned>(
+    document: Document,
+) -> Result<T, MemoryError> {
+    bson::from_document(document).map_err(MemoryError::Deserialization)
+}
@@ -0,0 +1,43 @@
+use mqb_core::EvalError;
+
+#[derive(Debug)]
+pub enum MemoryError {
+    Eval(EvalError),
+    Serialization(bson::ser::Error),
+    Deserialization(bson::de::Error),
+    DuplicateKey(bson::Bson),
+}
+
+impl std::fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryError::Eval(e) => write!(f, "{}", e),
+            MemoryError::Serialization(e) => {
+                write!(f, "failed to serialize: {}", e)
+            }
+            MemoryError::Deserialization(e) => {
+                write!(f, "failed to deserialize: {}", e)
+            }
+            MemoryError::DuplicateKey(id) => {
+                write!(f, "duplicate key: {{ _id: {} }}", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MemoryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MemoryError::Eval(e) => Some(e),
+            MemoryError::Serialization(e) => Some(e),
+            MemoryError::Deserialization(e) => Some(e),
+            MemoryError::DuplicateKey(_) => None,
+        }
+    }
+}
+
+impl From<EvalError> for MemoryError {
+    fn from(e: EvalError) -> Self {
+        MemoryError::Eval(e)
+    }
+}
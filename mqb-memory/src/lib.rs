@@ -0,0 +1,5 @@
+mod collection;
+mod error;
+
+pub use collection::*;
+pub use error::*;
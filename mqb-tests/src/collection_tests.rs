@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use bson::doc;
+use mongodb::{
+    options::{ClientOptions, ServerAddress},
+    Client,
+};
+use mqb_core::{
+    kp::KeyPathableAsRoot, CollectionError, FilterBuilder, TypedCollection,
+    UpdateBuilder,
+};
+use mqb_macro::KeyPathable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    #[serde(rename = "_id")]
+    id: i32,
+    customer: String,
+    items: Vec<LineItem>,
+}
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct LineItem {
+    sku: String,
+    status: String,
+}
+
+// The client connects lazily, so nothing here needs a running server.
+fn collection() -> TypedCollection<Order> {
+    let options = ClientOptions::builder()
+        .hosts(vec![ServerAddress::Tcp {
+            host: "localhost".to_string(),
+            port: Some(27017),
+        }])
+        .server_selection_timeout(Duration::from_millis(10))
+        .build();
+    let client = Client::with_options(options).unwrap();
+
+    client.database("shop").collection::<Order>("orders").into()
+}
+
+#[tokio::test]
+async fn test_builders_rooted_at_the_collection_type() {
+    let collection = collection();
+    assert_eq!(collection.inner().name(), "orders");
+
+    // Futures only run when awaited, so these only check the types.
+    drop(
+        collection.find(
+            FilterBuilder::new().eq(Order::kp().customer(), "Ada".into()),
+        ),
+    );
+    drop(collection.update_one(
+        FilterBuilder::new().eq(Order::kp().id(), 1),
+        UpdateBuilder::new().set(Order::kp().customer(), "Grace".to_string()),
+    ));
+    drop(collection.delete_many(FilterBuilder::new()));
+}
+
+#[tokio::test]
+async fn test_build_errors_are_reported_before_sending() {
+    let collection = collection();
+
+    let result = collection
+        .update_one(
+            FilterBuilder::new(),
+            UpdateBuilder::new()
+                .set(Order::kp().customer(), "Ada".to_string())
+                .set_on_insert(Order::kp().customer(), "Grace".to_string()),
+        )
+        .await;
+
+    assert!(matches!(result, Err(CollectionError::Build(_))));
+}
+
+#[test]
+fn test_update_options_carry_array_filters_only_when_used() {
+    let (_, options) = UpdateBuilder::<Order>::new()
+        .set(Order::kp().customer(), "Ada".to_string())
+        .try_build_with_options()
+        .unwrap();
+    assert_eq!(options.array_filters, None);
+
+    let (_, options) = UpdateBuilder::<Order>::new()
+        .set(
+            Order::kp().items().filtered("item").status(),
+            "shipped".to_string(),
+        )
+        .array_filter(
+            Order::kp().items(),
+            "item",
+            |f: FilterBuilder<LineItem>| {
+                f.eq(LineItem::kp().sku(), "A-1".to_string())
+            },
+        )
+        .try_build_with_options()
+        .unwrap();
+    assert_eq!(
+        options.array_filters,
+        Some(vec![doc! { "item.sku": { "$eq": "A-1" } }])
+    );
+}
@@ -0,0 +1,136 @@
+use mqb_core::{
+    kp::KeyPathableAsRoot, ErrorCause, Expr, FilterBuilder, Operand,
+    UpdateBuilder,
+};
+use mqb_macro::KeyPathable;
+use serde::{Serialize, Serializer};
+
+#[derive(Serialize, KeyPathable)]
+#[serde(rename_all = "PascalCase")]
+pub struct Customer {
+    name: String,
+    address: Address,
+    previous_addresses: Vec<Address>,
+    #[serde(serialize_with = "drop_all")]
+    tags: Vec<String>,
+}
+
+#[derive(Serialize, KeyPathable)]
+#[serde(rename_all = "PascalCase")]
+pub struct Address {
+    #[serde(serialize_with = "reject")]
+    city: String,
+    zip: String,
+}
+
+fn reject<S: Serializer>(city: &str, _: S) -> Result<S::Ok, S::Error> {
+    Err(serde::ser::Error::custom(format!(
+        "{} is not allowed",
+        city
+    )))
+}
+
+fn drop_all<S: Serializer>(_: &[String], s: S) -> Result<S::Ok, S::Error> {
+    s.collect_seq(std::iter::empty::<String>())
+}
+
+#[test]
+fn test_contains_reports_empty_serialization() {
+    let errors = FilterBuilder::<Customer>::new()
+        .contains(Customer::kp().tags(), "vip".to_string())
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Tags $eq: the item serialized to an empty array"
+    );
+}
+
+#[test]
+fn test_error_names_path_and_operator() {
+    let errors = FilterBuilder::<Customer>::new()
+        .r#in(Customer::kp().address().city(), ["Paris".to_string()])
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path(), Some("Address.City"));
+    assert_eq!(errors[0].operator(), "$in");
+    assert!(matches!(errors[0].cause(), ErrorCause::Serialization(_)));
+    assert_eq!(errors.to_string(), "Address.City $in: Paris is not allowed");
+}
+
+#[test]
+fn test_every_error_is_collected() {
+    let errors = FilterBuilder::<Customer>::new()
+        .eq(Customer::kp().address().city(), "Paris".to_string())
+        .eq(Customer::kp().name(), "Ada".to_string())
+        .ne(Customer::kp().address().city(), "Rome".to_string())
+        .or(Vec::<FilterBuilder<Customer>>::new())
+        .try_build()
+        .unwrap_err();
+
+    let messages = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "Address.City $eq: Paris is not allowed",
+            "Address.City $ne: Rome is not allowed",
+            "$or: requires at least one clause",
+        ]
+    );
+}
+
+#[test]
+fn test_nested_errors_are_relative_to_outer_path() {
+    let errors = FilterBuilder::<Customer>::new()
+        .elem_match(Customer::kp().previous_addresses(), |address| {
+            address.eq(Address::kp().city(), "Oslo".to_string())
+        })
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors[0].path(), Some("PreviousAddresses.City"));
+    assert_eq!(errors[0].operator(), "$eq");
+}
+
+#[test]
+fn test_expr_errors_are_collected() {
+    let errors = FilterBuilder::<Customer>::new()
+        .expr(Expr::eq(
+            Operand::field(Customer::kp().address().zip()),
+            Operand::value_for(
+                Customer::kp().address().zip(),
+                "1000".to_string(),
+            ),
+        ))
+        .not(|f| f.gt(Customer::kp().address().city(), "Bern".to_string()))
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].to_string(),
+        "Address.City $gt: Bern is not allowed"
+    );
+}
+
+#[test]
+fn test_update_errors_name_path_and_operator() {
+    let errors = UpdateBuilder::<Customer>::new()
+        .set(Customer::kp().address().city(), "Lima".to_string())
+        .set(Customer::kp().name(), "Ada".to_string())
+        .set_on_insert(Customer::kp().address().city(), "Kyiv".to_string())
+        .try_build()
+        .unwrap_err();
+
+    let messages = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "Address.City $set: Lima is not allowed",
+            "Address.City $setOnInsert: Kyiv is not allowed",
+        ]
+    );
+}
@@ -22,6 +22,18 @@ pub struct Person {
     pub address: Address,
 
     pub nickname: Option<String>,
+
+    pub permissions: i64,
+
+    pub spent: i64,
+
+    pub budget: i64,
+
+    pub tags: Vec<String>,
+
+    pub scores: Vec<i32>,
+
+    pub previous_addresses: Vec<Address>,
 }
 
 #[derive(Serialize, Deserialize, KeyPathable)]
@@ -408,3 +420,728 @@ fn all_field_types_combined_test() {
         }
     );
 }
+
+#[test]
+fn or_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .or([
+            mqb_core::FilterBuilder::new().lt(Person::kp().age(), 18),
+            mqb_core::FilterBuilder::new().gt(Person::kp().age(), 65),
+        ])
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "$or": [
+                { "Age": { "$lt": 18 } },
+                { "Age": { "$gt": 65 } }
+            ]
+        }
+    );
+}
+
+#[test]
+fn and_nor_with_closures_test() {
+    let city = "New York".to_string();
+
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .and([|f: mqb_core::FilterBuilder<Person>| {
+            f.eq(Person::kp().address().city(), city)
+        }])
+        .nor([|f: mqb_core::FilterBuilder<Person>| {
+            f.exists::<false, _, _>(Person::kp().nickname())
+        }])
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "$and": [
+                { "Address.City": { "$eq": "New York" } }
+            ],
+            "$nor": [
+                { "Nickname": { "$exists": false } }
+            ]
+        }
+    );
+}
+
+#[test]
+fn or_with_capturing_closures_test() {
+    let city = "New York".to_string();
+    let age = 30;
+
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .or_with(|clauses| {
+            clauses
+                .clause(|f: mqb_core::FilterBuilder<Person>| {
+                    f.eq(Person::kp().address().city(), city)
+                })
+                .clause(|f: mqb_core::FilterBuilder<Person>| {
+                    f.gt(Person::kp().age(), age)
+                })
+        })
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "$or": [
+                { "Address.City": { "$eq": "New York" } },
+                { "Age": { "$gt": 30 } }
+            ]
+        }
+    );
+}
+
+#[test]
+fn repeated_or_is_combined_under_and_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .or([
+            mqb_core::FilterBuilder::new().lt(Person::kp().age(), 18),
+            mqb_core::FilterBuilder::new().gt(Person::kp().age(), 65),
+        ])
+        .or([
+            mqb_core::FilterBuilder::new()
+                .eq(Person::kp().address().kind(), AddressKind::Home),
+            mqb_core::FilterBuilder::new()
+                .exists::<true, _, _>(Person::kp().nickname()),
+        ])
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "$or": [
+                { "Age": { "$lt": 18 } },
+                { "Age": { "$gt": 65 } }
+            ],
+            "$and": [
+                {
+                    "$or": [
+                        { "Address.Kind": { "$eq": AddressKind::Home as i32 } },
+                        { "Nickname": { "$exists": true } }
+                    ]
+                }
+            ]
+        }
+    );
+}
+
+#[test]
+fn empty_or_is_an_error_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .or(Vec::<mqb_core::FilterBuilder<Person>>::new())
+        .try_build();
+
+    assert!(filter.is_err());
+}
+
+#[test]
+fn not_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .not(|f| f.gt(Person::kp().age(), 65))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Age": {
+                "$not": { "$gt": 65 }
+            }
+        }
+    );
+}
+
+#[test]
+fn not_alongside_other_operators_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .gt(Person::kp().age(), 18)
+        .not(|f| {
+            f.eq(Person::kp().age(), 21)
+                .r#in(Person::kp().address().kind(), vec![AddressKind::Work])
+        })
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Age": {
+                "$gt": 18,
+                "$not": { "$eq": 21 }
+            },
+            "Address.Kind": {
+                "$not": { "$in": [AddressKind::Work as i32] }
+            }
+        }
+    );
+}
+
+#[test]
+fn repeated_not_on_same_path_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .not(|f| f.eq(Person::kp().age(), 21))
+        .not(|f| f.eq(Person::kp().age(), 30))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Age": {
+                "$not": { "$eq": 21 }
+            },
+            "$and": [
+                { "Age": { "$not": { "$eq": 30 } } }
+            ]
+        }
+    );
+}
+
+#[test]
+fn not_of_logical_operator_is_an_error_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .not(|f| {
+            f.or([mqb_core::FilterBuilder::new().gt(Person::kp().age(), 1)])
+        })
+        .try_build();
+
+    assert!(filter.is_err());
+}
+
+#[test]
+fn in_accepts_any_iterator_test() {
+    let oids = [ObjectId::new(), ObjectId::new()];
+    let ids = std::collections::BTreeSet::from(oids);
+
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .r#in(Person::kp().id(), ids.iter().copied())
+        .r#in(Person::kp().age(), [18_i16, 21])
+        .try_build()
+        .unwrap();
+
+    let expected_ids = ids.iter().map(|id| bson::Bson::ObjectId(*id));
+
+    assert_eq!(
+        filter,
+        doc! {
+            "_id": {
+                "$in": expected_ids.collect::<Vec<_>>()
+            },
+            "Age": {
+                "$in": [18, 21]
+            }
+        }
+    );
+}
+
+#[test]
+fn nin_uses_key_path_serializer_test() {
+    let oid = ObjectId::new();
+
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .nin(
+            Person::kp().profile_id(),
+            std::collections::HashSet::from([oid]),
+        )
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "ProfileId": {
+                "$nin": [oid.to_hex()]
+            }
+        }
+    );
+}
+
+#[test]
+fn regex_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .regex(
+            Person::kp().address().city(),
+            "^new",
+            mqb_core::RegexOptions {
+                case_insensitive: true,
+                multiline: true,
+                ..Default::default()
+            },
+        )
+        .regex(
+            Person::kp().nickname(),
+            mqb_core::prefix_regex("J.R. (Bob)"),
+            mqb_core::RegexOptions::default(),
+        )
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Address.City": {
+                "$regex": "^new",
+                "$options": "im"
+            },
+            "Nickname": {
+                "$regex": r"^J\.R\.\ \(Bob\)"
+            }
+        }
+    );
+}
+
+#[test]
+fn not_regex_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .not(|f| {
+            f.regex(
+                Person::kp().address().street(),
+                "avenue$",
+                mqb_core::RegexOptions::case_insensitive(),
+            )
+        })
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Address.Street": {
+                "$not": {
+                    "$regex": "avenue$",
+                    "$options": "i"
+                }
+            }
+        }
+    );
+}
+
+#[test]
+fn all_and_size_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .all(Person::kp().tags(), ["admin", "staff"])
+        .size(Person::kp().previous_addresses(), 2)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Tags": {
+                "$all": ["admin", "staff"]
+            },
+            "PreviousAddresses": {
+                "$size": 2_i64
+            }
+        }
+    );
+}
+
+#[test]
+fn elem_match_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .elem_match(Person::kp().previous_addresses(), |f| {
+            f.eq(Address::kp().city(), "Paris".to_string())
+                .ne(Address::kp().kind(), AddressKind::Work)
+        })
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "PreviousAddresses": {
+                "$elemMatch": {
+                    "City": { "$eq": "Paris" },
+                    "Kind": { "$ne": AddressKind::Work as i32 }
+                }
+            }
+        }
+    );
+}
+
+#[test]
+fn elem_match_value_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .elem_match_value(Person::kp().scores(), |e| e.gte(80).lt(85))
+        .elem_match_value(Person::kp().tags(), |e| e.r#in(["a", "b"]))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Scores": { "$elemMatch": { "$gte": 80, "$lt": 85 } },
+            "Tags": { "$elemMatch": { "$in": ["a", "b"] } }
+        }
+    );
+
+    let errors = mqb_core::FilterBuilder::<Person>::new()
+        .elem_match_value(Person::kp().scores(), |e| e.gte(80).gte(85))
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors.to_string(), "Scores $gte: duplicate operator");
+}
+
+#[test]
+fn contains_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .contains(Person::kp().tags(), "admin")
+        .not(|f| {
+            f.contains(
+                Person::kp().previous_addresses(),
+                Address {
+                    city: "Paris".to_string(),
+                    street: "Rue de Rivoli".to_string(),
+                    kind: AddressKind::Home,
+                },
+            )
+        })
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Tags": {
+                "$eq": "admin"
+            },
+            "PreviousAddresses": {
+                "$not": {
+                    "$eq": {
+                        "City": "Paris",
+                        "Street": "Rue de Rivoli",
+                        "Kind": AddressKind::Home as i32
+                    }
+                }
+            }
+        }
+    );
+}
+
+#[test]
+fn type_is_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .type_is(Person::kp().age(), mqb_core::BsonType::Long)
+        .type_is(Person::kp().address(), mqb_core::BsonType::Object)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Age": {
+                "$type": "long"
+            },
+            "Address": {
+                "$type": "object"
+            }
+        }
+    );
+}
+
+#[test]
+fn modulo_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .modulo(Person::kp().age(), 10, 0)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Age": {
+                "$mod": [10_i64, 0_i64]
+            }
+        }
+    );
+}
+
+#[test]
+fn modulo_by_zero_is_an_error_test() {
+    let errors = mqb_core::FilterBuilder::<Person>::new()
+        .modulo(Person::kp().age(), 0, 1)
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors.to_string(), "Age $mod: the divisor cannot be zero");
+}
+
+#[test]
+fn bits_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .bits_all_set(Person::kp().permissions(), 0b101)
+        .bits_any_clear(Person::kp().permissions(), [1, 3])
+        .bits_any_set(Person::kp().age(), vec![0])
+        .bits_all_clear(Person::kp().age(), 1_i64 << 40)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Permissions": {
+                "$bitsAllSet": 5_i64,
+                "$bitsAnyClear": [1_i64, 3_i64]
+            },
+            "Age": {
+                "$bitsAnySet": [0_i64],
+                "$bitsAllClear": 1_i64 << 40
+            }
+        }
+    );
+}
+
+#[test]
+fn text_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .text(
+            "coffee -decaf",
+            mqb_core::TextSearchOptions {
+                language: Some("en".to_string()),
+                diacritic_sensitive: true,
+                ..Default::default()
+            },
+        )
+        .gt(Person::kp().age(), 18)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "$text": {
+                "$search": "coffee -decaf",
+                "$language": "en",
+                "$diacriticSensitive": true
+            },
+            "Age": {
+                "$gt": 18
+            }
+        }
+    );
+}
+
+#[test]
+fn repeated_text_is_an_error_test() {
+    let errors = mqb_core::FilterBuilder::<Person>::new()
+        .text("a", Default::default())
+        .text("b", Default::default())
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors.to_string(), "$text: duplicate operator");
+}
+
+#[test]
+fn nested_text_is_an_error_test() {
+    let errors = mqb_core::FilterBuilder::<Person>::new()
+        .elem_match(Person::kp().previous_addresses(), |f| {
+            f.text("paris", Default::default())
+        })
+        .nor([|f: mqb_core::FilterBuilder<Person>| {
+            f.text("london", Default::default())
+        }])
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "PreviousAddresses $text: only allowed at the top level of a query \
+         or in $and/$or; $text: only allowed at the top level of a query or \
+         in $and/$or"
+    );
+
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .or([|f: mqb_core::FilterBuilder<Person>| {
+            f.text("london", Default::default())
+        }])
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! { "$or": [{ "$text": { "$search": "london" } }] }
+    );
+}
+
+#[test]
+fn expr_field_comparison_test() {
+    use mqb_core::{Expr, Operand};
+
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .expr(Expr::gt(
+            Operand::field(Person::kp().spent()),
+            Operand::field(Person::kp().budget()),
+        ))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "$expr": {
+                "$gt": ["$Spent", "$Budget"]
+            }
+        }
+    );
+}
+
+#[test]
+fn expr_value_uses_field_serializer_test() {
+    use mqb_core::{Expr, Operand};
+
+    let now = Utc::now();
+
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .expr(Expr::gt(
+            Operand::field(Person::kp().time()),
+            Operand::value_for(Person::kp().time(), now),
+        ))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "$expr": {
+                "$gt": [
+                    "$Time",
+                    { "$literal": bson::DateTime::from_chrono(now) }
+                ]
+            }
+        }
+    );
+}
+
+#[test]
+fn expr_logical_test() {
+    use mqb_core::{Expr, Operand};
+
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .expr(Expr::or([
+            Expr::lte(
+                Operand::field(Person::kp().spent()),
+                Operand::value_for(Person::kp().spent(), 0),
+            ),
+            !Expr::eq(
+                Operand::field(Person::kp().address().city()),
+                Operand::field(Person::kp().address().street()),
+            ),
+        ]))
+        .expr(Expr::gte(
+            Operand::field(Person::kp().budget()),
+            Operand::value_for(Person::kp().budget(), 100),
+        ))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "$expr": {
+                "$or": [
+                    { "$lte": ["$Spent", { "$literal": 0_i64 }] },
+                    { "$not": [{ "$eq": ["$Address.City", "$Address.Street"] }] }
+                ]
+            },
+            "$and": [
+                {
+                    "$expr": {
+                        "$gte": ["$Budget", { "$literal": 100_i64 }]
+                    }
+                }
+            ]
+        }
+    );
+}
+
+#[test]
+fn duplicate_operator_is_an_error_test() {
+    let errors = mqb_core::FilterBuilder::<Person>::new()
+        .eq(Person::kp().age(), 1)
+        .gt(Person::kp().age(), 0)
+        .eq(Person::kp().age(), 2)
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors.to_string(), "Age $eq: duplicate operator");
+}
+
+#[test]
+fn duplicate_operator_in_nested_clause_is_an_error_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .elem_match(Person::kp().previous_addresses(), |f| {
+            f.eq(Address::kp().city(), "Paris".to_string())
+                .eq(Address::kp().city(), "Rome".to_string())
+        })
+        .try_build();
+
+    assert!(filter.is_err());
+}
+
+#[test]
+fn lenient_mode_combines_duplicates_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .mode(mqb_core::FilterMode::Lenient)
+        .eq(Person::kp().age(), 1)
+        .eq(Person::kp().age(), 2)
+        .regex(
+            Person::kp().nickname(),
+            "^a",
+            mqb_core::RegexOptions::default(),
+        )
+        .regex(
+            Person::kp().nickname(),
+            "z$",
+            mqb_core::RegexOptions::case_insensitive(),
+        )
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "Age": { "$eq": 1 },
+            "$and": [
+                { "Age": { "$eq": 2 } },
+                { "Nickname": { "$regex": "z$", "$options": "i" } }
+            ],
+            "Nickname": { "$regex": "^a" }
+        }
+    );
+}
+
+#[test]
+fn lenient_mode_applies_to_nested_clauses_test() {
+    let filter = mqb_core::FilterBuilder::<Person>::new()
+        .mode(mqb_core::FilterMode::Lenient)
+        .elem_match(Person::kp().previous_addresses(), |f| {
+            f.eq(Address::kp().city(), "Paris".to_string())
+                .eq(Address::kp().city(), "Rome".to_string())
+        })
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "PreviousAddresses": {
+                "$elemMatch": {
+                    "City": { "$eq": "Paris" },
+                    "$and": [{ "City": { "$eq": "Rome" } }]
+                }
+            }
+        }
+    );
+}
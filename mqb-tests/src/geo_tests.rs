@@ -0,0 +1,148 @@
+use bson::doc;
+use mqb_core::{
+    geo::{MultiPolygon, Point, Polygon},
+    kp::KeyPathableAsRoot,
+    FilterBuilder,
+};
+use mqb_macro::KeyPathable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Store {
+    name: String,
+    location: Point,
+    delivery_area: Polygon,
+}
+
+fn square(origin: f64, side: f64) -> Polygon {
+    Polygon::new(vec![
+        [origin, origin],
+        [origin + side, origin],
+        [origin + side, origin + side],
+        [origin, origin + side],
+        [origin, origin],
+    ])
+}
+
+#[test]
+fn geojson_serde_test() {
+    let point = Point::new(-73.97, 40.77);
+
+    let bson = bson::to_bson(&point).unwrap();
+
+    assert_eq!(
+        bson,
+        bson::Bson::Document(doc! {
+            "type": "Point",
+            "coordinates": [-73.97, 40.77]
+        })
+    );
+    assert_eq!(bson::from_bson::<Point>(bson).unwrap(), point);
+}
+
+#[test]
+fn geo_within_test() {
+    let area = MultiPolygon::new([square(0.0, 1.0), square(5.0, 1.0)]);
+
+    let filter = FilterBuilder::<Store>::new()
+        .geo_within(Store::kp().location(), area)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "location": {
+                "$geoWithin": {
+                    "$geometry": {
+                        "type": "MultiPolygon",
+                        "coordinates": [
+                            [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]],
+                            [[[5.0, 5.0], [6.0, 5.0], [6.0, 6.0], [5.0, 6.0], [5.0, 5.0]]]
+                        ]
+                    }
+                }
+            }
+        }
+    );
+}
+
+#[test]
+fn geo_intersects_test() {
+    let filter = FilterBuilder::<Store>::new()
+        .geo_intersects(Store::kp().delivery_area(), Point::new(0.5, 0.5))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "deliveryArea": {
+                "$geoIntersects": {
+                    "$geometry": {
+                        "type": "Point",
+                        "coordinates": [0.5, 0.5]
+                    }
+                }
+            }
+        }
+    );
+}
+
+#[test]
+fn near_test() {
+    let filter = FilterBuilder::<Store>::new()
+        .near(
+            Store::kp().location(),
+            Point::new(1.0, 2.0),
+            Some(500.0),
+            None,
+        )
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "location": {
+                "$near": {
+                    "$geometry": {
+                        "type": "Point",
+                        "coordinates": [1.0, 2.0]
+                    },
+                    "$maxDistance": 500.0
+                }
+            }
+        }
+    );
+}
+
+#[test]
+fn near_sphere_test() {
+    let filter = FilterBuilder::<Store>::new()
+        .near_sphere(
+            Store::kp().location(),
+            Point::new(1.0, 2.0),
+            Some(1000.0),
+            Some(10.0),
+        )
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        filter,
+        doc! {
+            "location": {
+                "$nearSphere": {
+                    "$geometry": {
+                        "type": "Point",
+                        "coordinates": [1.0, 2.0]
+                    },
+                    "$maxDistance": 1000.0,
+                    "$minDistance": 10.0
+                }
+            }
+        }
+    );
+}
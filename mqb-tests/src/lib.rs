@@ -1,9 +1,36 @@
+#[cfg(test)]
+mod collection_tests;
+
+#[cfg(test)]
+mod error_tests;
+
 #[cfg(test)]
 mod filter_tests;
 
+#[cfg(test)]
+mod geo_tests;
+
+#[cfg(test)]
+mod matcher_tests;
+
+#[cfg(test)]
+mod memory_tests;
+
+#[cfg(test)]
+mod projection_tests;
+
+#[cfg(test)]
+mod schema_tests;
+
+#[cfg(test)]
+mod sort_tests;
+
 #[cfg(test)]
 mod update_tests;
 
+#[cfg(test)]
+mod updater_tests;
+
 pub mod object_id_as_hex_string {
     use bson::oid::ObjectId;
     use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
@@ -0,0 +1,267 @@
+use bson::doc;
+use mqb_core::{
+    geo::{Point, Polygon},
+    kp::KeyPathableAsRoot,
+    BsonType, Expr, FilterBuilder, Matcher, Operand, RegexOptions,
+    TextSearchOptions,
+};
+use mqb_macro::KeyPathable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    name: String,
+    age: i32,
+    nickname: Option<String>,
+    tags: Vec<String>,
+    scores: Vec<i32>,
+    flags: i64,
+    spent: i64,
+    budget: i64,
+    location: Point,
+    contacts: Vec<Contact>,
+}
+
+#[derive(Clone, Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Contact {
+    kind: String,
+    value: String,
+}
+
+fn account() -> Account {
+    Account {
+        name: "Ada Lovelace".to_string(),
+        age: 36,
+        nickname: None,
+        tags: vec!["admin".to_string(), "math".to_string()],
+        scores: vec![78, 90],
+        flags: 0b1010,
+        spent: 120,
+        budget: 100,
+        location: Point::new(0.5, 0.5),
+        contacts: vec![
+            Contact {
+                kind: "email".to_string(),
+                value: "ada@example.com".to_string(),
+            },
+            Contact {
+                kind: "phone".to_string(),
+                value: "555-0100".to_string(),
+            },
+        ],
+    }
+}
+
+fn matches(filter: FilterBuilder<Account>) -> bool {
+    Matcher::new(filter).unwrap().matches(&account())
+}
+
+#[test]
+fn comparison_test() {
+    assert!(matches(
+        FilterBuilder::new()
+            .gt(Account::kp().age(), 30)
+            .lte(Account::kp().age(), 36)
+    ));
+    assert!(!matches(FilterBuilder::new().ne(Account::kp().age(), 36)));
+    assert!(matches(
+        FilterBuilder::new().r#in(Account::kp().age(), [1, 36])
+    ));
+    assert!(matches(
+        FilterBuilder::new().nin(Account::kp().age(), [1, 2])
+    ));
+}
+
+#[test]
+fn comparison_is_bracketed_by_type_test() {
+    let matcher = Matcher::<Account>::new(
+        FilterBuilder::new().gt(Account::kp().age(), 30),
+    )
+    .unwrap();
+
+    assert!(matcher.matches_document(&doc! { "age": 31.5 }));
+    assert!(matcher.matches_document(&doc! { "age": 31_i64 }));
+    assert!(!matcher.matches_document(&doc! { "age": "40" }));
+    assert!(!matcher.matches_document(&doc! {}));
+}
+
+#[test]
+fn missing_and_null_test() {
+    let is_none = Matcher::<Account>::new(
+        FilterBuilder::new().eq(Account::kp().nickname(), None),
+    )
+    .unwrap();
+    let exists = Matcher::<Account>::new(
+        FilterBuilder::new().exists::<true, _, _>(Account::kp().nickname()),
+    )
+    .unwrap();
+
+    assert!(is_none.matches_document(&doc! {}));
+    assert!(is_none.matches_document(&doc! { "nickname": null }));
+    assert!(!is_none.matches_document(&doc! { "nickname": "Ada" }));
+
+    assert!(!exists.matches_document(&doc! {}));
+    assert!(exists.matches_document(&doc! { "nickname": null }));
+}
+
+#[test]
+fn array_test() {
+    assert!(matches(
+        FilterBuilder::new().contains(Account::kp().tags(), "math")
+    ));
+    assert!(matches(
+        FilterBuilder::new().all(Account::kp().tags(), ["math", "admin"])
+    ));
+    assert!(!matches(
+        FilterBuilder::new().all(Account::kp().tags(), ["math", "art"])
+    ));
+    assert!(matches(FilterBuilder::new().size(Account::kp().tags(), 2)));
+    assert!(matches(
+        FilterBuilder::new()
+            .elem_match_value(Account::kp().scores(), |e| e.gte(85).lt(95))
+    ));
+    assert!(!matches(
+        FilterBuilder::new()
+            .elem_match_value(Account::kp().scores(), |e| e.gte(80).lt(85))
+    ));
+    assert!(matches(FilterBuilder::new().elem_match(
+        Account::kp().contacts(),
+        |f| {
+            f.eq(Contact::kp().kind(), "phone".to_string()).regex(
+                Contact::kp().value(),
+                "^555",
+                RegexOptions::default(),
+            )
+        }
+    )));
+    assert!(!matches(FilterBuilder::new().elem_match(
+        Account::kp().contacts(),
+        |f| {
+            f.eq(Contact::kp().kind(), "phone".to_string()).regex(
+                Contact::kp().value(),
+                "@",
+                RegexOptions::default(),
+            )
+        }
+    )));
+}
+
+#[test]
+fn logical_and_negation_test() {
+    assert!(matches(FilterBuilder::new().or([
+        FilterBuilder::new().lt(Account::kp().age(), 18),
+        FilterBuilder::new().contains(Account::kp().tags(), "admin"),
+    ])));
+    assert!(!matches(FilterBuilder::new().nor([
+        FilterBuilder::new().contains(Account::kp().tags(), "admin")
+    ])));
+    assert!(matches(FilterBuilder::new().not(|f| {
+        f.regex(
+            Account::kp().name(),
+            "^ada$",
+            RegexOptions::case_insensitive(),
+        )
+    })));
+    assert!(matches(FilterBuilder::new().regex(
+        Account::kp().name(),
+        mqb_core::prefix_regex("ada love"),
+        RegexOptions::case_insensitive(),
+    )));
+}
+
+#[test]
+fn type_mod_and_bits_test() {
+    assert!(matches(
+        FilterBuilder::new().type_is(Account::kp().age(), BsonType::Int)
+    ));
+    assert!(matches(
+        FilterBuilder::new().type_is(Account::kp().age(), BsonType::Number)
+    ));
+    assert!(!matches(
+        FilterBuilder::new().type_is(Account::kp().flags(), BsonType::Int)
+    ));
+    assert!(matches(FilterBuilder::new().modulo(
+        Account::kp().age(),
+        6,
+        0
+    )));
+    assert!(matches(
+        FilterBuilder::new()
+            .bits_all_set(Account::kp().flags(), [1, 3])
+            .bits_all_clear(Account::kp().flags(), 0b0101)
+            .bits_any_set(Account::kp().flags(), 0b11)
+            .bits_any_clear(Account::kp().flags(), [1, 2])
+    ));
+    assert!(!matches(
+        FilterBuilder::new().bits_any_set(Account::kp().flags(), 0b0101)
+    ));
+}
+
+#[test]
+fn expr_test() {
+    assert!(matches(FilterBuilder::new().expr(Expr::gt(
+        Operand::field(Account::kp().spent()),
+        Operand::field(Account::kp().budget()),
+    ))));
+    assert!(!matches(FilterBuilder::new().expr(Expr::and([
+        Expr::gt(
+            Operand::field(Account::kp().spent()),
+            Operand::field(Account::kp().budget()),
+        ),
+        Expr::lt(
+            Operand::field(Account::kp().age()),
+            Operand::value_for(Account::kp().age(), 30),
+        ),
+    ]))));
+}
+
+#[test]
+fn text_test() {
+    let text = |search: &str| {
+        matches(FilterBuilder::new().text(search, TextSearchOptions::default()))
+    };
+
+    assert!(text("lovelace babbage"));
+    assert!(text("\"ada lovelace\""));
+    assert!(!text("babbage"));
+    assert!(!text("ada -admin"));
+}
+
+#[test]
+fn geo_test() {
+    let square = Polygon::new(vec![
+        [0.0, 0.0],
+        [1.0, 0.0],
+        [1.0, 1.0],
+        [0.0, 1.0],
+        [0.0, 0.0],
+    ]);
+
+    assert!(matches(
+        FilterBuilder::new().geo_within(Account::kp().location(), square)
+    ));
+    assert!(matches(FilterBuilder::new().near_sphere(
+        Account::kp().location(),
+        Point::new(0.5, 0.6),
+        Some(20_000.0),
+        None,
+    )));
+    assert!(!matches(FilterBuilder::new().near(
+        Account::kp().location(),
+        Point::new(0.5, 0.6),
+        Some(20_000.0),
+        Some(15_000.0),
+    )));
+}
+
+#[test]
+fn json_schema_test() {
+    let matcher =
+        Matcher::<Contact>::new(FilterBuilder::new().json_schema()).unwrap();
+
+    assert!(matcher.matches_document(&doc! { "kind": "email", "value": "x" }));
+    assert!(!matcher.matches_document(&doc! { "kind": "email" }));
+    assert!(!matcher.matches_document(&doc! { "kind": 1, "value": "x" }));
+}
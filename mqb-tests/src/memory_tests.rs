@@ -0,0 +1,355 @@
+use bson::Bson;
+use mqb_core::{
+    geo::Point, kp::KeyPathableAsRoot, FilterBuilder, UpdateBuilder,
+};
+use mqb_macro::KeyPathable;
+use mqb_memory::{MemoryCollection, MemoryError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    #[serde(rename = "_id")]
+    id: i32,
+    title: String,
+    done: bool,
+    priority: i32,
+    labels: Vec<String>,
+}
+
+fn task(id: i32, title: &str, priority: i32) -> Task {
+    Task {
+        id,
+        title: title.to_string(),
+        done: false,
+        priority,
+        labels: Vec::new(),
+    }
+}
+
+fn collection() -> MemoryCollection<Task> {
+    let collection = MemoryCollection::new();
+    collection
+        .insert_many(&[
+            task(1, "write", 2),
+            task(2, "review", 1),
+            task(3, "ship", 3),
+        ])
+        .unwrap();
+    collection
+}
+
+#[test]
+fn test_find() {
+    let collection = collection();
+
+    let tasks = collection
+        .find(FilterBuilder::new().gte(Task::kp().priority(), 2))
+        .unwrap();
+    assert_eq!(tasks, vec![task(1, "write", 2), task(3, "ship", 3)]);
+
+    let task = collection
+        .find_one(FilterBuilder::new().eq(Task::kp().title(), "review".into()))
+        .unwrap();
+    assert_eq!(task.map(|task| task.id), Some(2));
+
+    let count = collection
+        .count(FilterBuilder::new().eq(Task::kp().done(), true))
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_update_one_and_many() {
+    let collection = collection();
+
+    let result = collection
+        .update_one(
+            FilterBuilder::new().gte(Task::kp().priority(), 1),
+            UpdateBuilder::new().set(Task::kp().done(), true),
+        )
+        .unwrap();
+    assert_eq!((result.matched_count, result.modified_count), (1, 1));
+
+    let result = collection
+        .update_many(
+            FilterBuilder::new().gte(Task::kp().priority(), 1),
+            UpdateBuilder::new().set(Task::kp().done(), true),
+        )
+        .unwrap();
+    assert_eq!((result.matched_count, result.modified_count), (3, 2));
+
+    let done = collection
+        .count(FilterBuilder::new().eq(Task::kp().done(), true))
+        .unwrap();
+    assert_eq!(done, 3);
+}
+
+#[test]
+fn test_upsert_seeds_from_filter() {
+    let collection = collection();
+
+    let result = collection
+        .upsert(
+            FilterBuilder::new()
+                .eq(Task::kp().id(), 4)
+                .eq(Task::kp().title(), "deploy".into()),
+            UpdateBuilder::new()
+                .set_on_insert(Task::kp().priority(), 5)
+                .set_on_insert(Task::kp().done(), false)
+                .push(Task::kp().labels(), "ops".to_string()),
+        )
+        .unwrap();
+    assert_eq!(result.matched_count, 0);
+    assert_eq!(result.upserted_id, Some(Bson::Int32(4)));
+
+    let upserted = collection
+        .find_one(FilterBuilder::new().eq(Task::kp().id(), 4))
+        .unwrap()
+        .unwrap();
+    assert_eq!(upserted.title, "deploy");
+    assert_eq!(upserted.priority, 5);
+    assert_eq!(upserted.labels, vec!["ops"]);
+
+    let result = collection
+        .upsert(
+            FilterBuilder::new().eq(Task::kp().id(), 4),
+            UpdateBuilder::new().set_on_insert(Task::kp().priority(), 9),
+        )
+        .unwrap();
+    assert_eq!((result.matched_count, result.modified_count), (1, 0));
+    assert_eq!(result.upserted_id, None);
+}
+
+#[test]
+fn test_concurrent_upserts_insert_once() {
+    let collection = collection();
+
+    std::thread::scope(|scope| {
+        for id in 10..18 {
+            let collection = &collection;
+            scope.spawn(move || {
+                collection
+                    .upsert(
+                        FilterBuilder::new()
+                            .eq(Task::kp().title(), "deploy".into()),
+                        UpdateBuilder::new()
+                            .set_on_insert(Task::kp().id(), id)
+                            .set_on_insert(Task::kp().priority(), 1)
+                            .set_on_insert(Task::kp().done(), false)
+                            .set_on_insert(Task::kp().labels(), Vec::new()),
+                    )
+                    .unwrap();
+            });
+        }
+    });
+
+    let count = collection
+        .count(FilterBuilder::new().eq(Task::kp().title(), "deploy".into()))
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_delete() {
+    let collection = collection();
+
+    let deleted = collection
+        .delete_one(FilterBuilder::new().lte(Task::kp().priority(), 2))
+        .unwrap();
+    assert_eq!(deleted, 1);
+
+    let deleted = collection
+        .delete_many(FilterBuilder::new().gte(Task::kp().priority(), 0))
+        .unwrap();
+    assert_eq!(deleted, 2);
+    assert!(collection.is_empty());
+}
+
+#[test]
+fn test_duplicate_id_is_rejected() {
+    let collection = collection();
+
+    let result = collection.insert_one(&task(1, "again", 0));
+    assert!(matches!(result, Err(MemoryError::DuplicateKey(_))));
+    assert_eq!(collection.len(), 3);
+}
+
+#[test]
+fn test_failed_update_leaves_collection_untouched() {
+    let collection = collection();
+
+    let result = collection.update_many(
+        FilterBuilder::new(),
+        UpdateBuilder::new()
+            .set(Task::kp().title(), "same".to_string())
+            .set(Task::kp().id(), 10),
+    );
+    assert!(result.is_err());
+
+    let titles = collection
+        .find(FilterBuilder::new())
+        .unwrap()
+        .into_iter()
+        .map(|task| task.title)
+        .collect::<Vec<_>>();
+    assert_eq!(titles, vec!["write", "review", "ship"]);
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    #[serde(rename = "_id")]
+    id: i32,
+    items: Vec<LineItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct LineItem {
+    sku: String,
+    quantity: i32,
+    status: String,
+}
+
+fn line_item(sku: &str, quantity: i32) -> LineItem {
+    LineItem {
+        sku: sku.to_string(),
+        quantity,
+        status: "pending".to_string(),
+    }
+}
+
+fn statuses(collection: &MemoryCollection<Order>) -> Vec<String> {
+    collection
+        .find_one(FilterBuilder::new())
+        .unwrap()
+        .unwrap()
+        .items
+        .into_iter()
+        .map(|item| item.status)
+        .collect()
+}
+
+#[test]
+fn test_positional_updates() {
+    let collection = MemoryCollection::new();
+    collection
+        .insert_one(&Order {
+            id: 1,
+            items: vec![
+                line_item("a", 1),
+                line_item("b", 5),
+                line_item("c", 9),
+            ],
+        })
+        .unwrap();
+
+    collection
+        .update_one(
+            FilterBuilder::new().elem_match(
+                Order::kp().items(),
+                |f: FilterBuilder<LineItem>| {
+                    f.eq(LineItem::kp().sku(), "b".to_string())
+                },
+            ),
+            UpdateBuilder::new().set(
+                Order::kp().items().first_match().status(),
+                "shipped".to_string(),
+            ),
+        )
+        .unwrap();
+    assert_eq!(statuses(&collection), vec!["pending", "shipped", "pending"]);
+
+    collection
+        .update_one(
+            FilterBuilder::new(),
+            UpdateBuilder::new()
+                .set(
+                    Order::kp().items().filtered("large").status(),
+                    "split".to_string(),
+                )
+                .array_filter(
+                    Order::kp().items(),
+                    "large",
+                    |f: FilterBuilder<LineItem>| {
+                        f.gt(LineItem::kp().quantity(), 4)
+                    },
+                ),
+        )
+        .unwrap();
+    assert_eq!(statuses(&collection), vec!["pending", "split", "split"]);
+
+    collection
+        .update_one(
+            FilterBuilder::new(),
+            UpdateBuilder::new().inc(Order::kp().items().all().quantity(), 1),
+        )
+        .unwrap();
+    let quantities = collection
+        .find_one(FilterBuilder::new())
+        .unwrap()
+        .unwrap()
+        .items
+        .into_iter()
+        .map(|item| item.quantity)
+        .collect::<Vec<_>>();
+    assert_eq!(quantities, vec![2, 6, 10]);
+
+    // `$` needs the query to have matched an element.
+    let result = collection.update_one(
+        FilterBuilder::new(),
+        UpdateBuilder::new().set(
+            Order::kp().items().first_match().status(),
+            "lost".to_string(),
+        ),
+    );
+    assert!(result.is_err());
+}
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+pub struct Shop {
+    #[serde(rename = "_id")]
+    id: i32,
+    location: Point,
+}
+
+#[test]
+fn test_near_returns_nearest_first() {
+    let collection = MemoryCollection::<Shop>::new();
+    collection
+        .insert_many(&[
+            Shop {
+                id: 1,
+                location: Point::new(0.3, 0.0),
+            },
+            Shop {
+                id: 2,
+                location: Point::new(0.1, 0.0),
+            },
+            Shop {
+                id: 3,
+                location: Point::new(0.2, 0.0),
+            },
+        ])
+        .unwrap();
+
+    let near = || {
+        FilterBuilder::new().near_sphere(
+            Shop::kp().location(),
+            Point::new(0.0, 0.0),
+            None,
+            None,
+        )
+    };
+
+    let ids = collection
+        .find(near())
+        .unwrap()
+        .into_iter()
+        .map(|shop| shop.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![2, 3, 1]);
+
+    assert_eq!(collection.find_one(near()).unwrap().unwrap().id, 2);
+}
@@ -0,0 +1,57 @@
+use bson::doc;
+use mqb_core::{kp::KeyPathableAsRoot, Meta, ProjectionBuilder};
+use mqb_macro::KeyPathable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Article {
+    title: String,
+    body: String,
+    author: Author,
+}
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Author {
+    display_name: String,
+    email: String,
+}
+
+#[test]
+fn include_exclude_test() {
+    let projection = ProjectionBuilder::<Article>::new()
+        .include(Article::kp().title())
+        .include(Article::kp().author().display_name())
+        .build();
+
+    assert_eq!(
+        projection,
+        doc! {
+            "title": 1,
+            "author.displayName": 1
+        }
+    );
+
+    let projection = ProjectionBuilder::<Article>::new()
+        .exclude(Article::kp().body())
+        .build();
+
+    assert_eq!(projection, doc! { "body": 0 });
+}
+
+#[test]
+fn text_score_meta_test() {
+    let projection = ProjectionBuilder::<Article>::new()
+        .include(Article::kp().title())
+        .meta("score", Meta::TextScore)
+        .build();
+
+    assert_eq!(
+        projection,
+        doc! {
+            "title": 1,
+            "score": { "$meta": "textScore" }
+        }
+    );
+}
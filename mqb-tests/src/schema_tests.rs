@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use bson::{doc, oid::ObjectId};
+use chrono::{DateTime, Utc};
+use mqb_core::{FilterBuilder, FilterMode, JsonSchema, Matcher};
+use mqb_macro::KeyPathable;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "PascalCase")]
+pub struct Person {
+    #[serde(rename = "_id")]
+    id: ObjectId,
+
+    #[serde(with = "crate::object_id_as_hex_string")]
+    profile_id: ObjectId,
+
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub time: DateTime<Utc>,
+
+    pub age: i32,
+
+    pub address: Address,
+
+    pub nickname: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    pub attempts: HashMap<String, Vec<i64>>,
+}
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "PascalCase")]
+pub struct Address {
+    city: String,
+    kind: AddressKind,
+    status: AddressStatus,
+}
+
+#[derive(Serialize_repr, Deserialize_repr, KeyPathable, PartialEq)]
+#[repr(u8)]
+pub enum AddressKind {
+    Home = 0,
+    Work = 1,
+}
+
+#[derive(Serialize, Deserialize, KeyPathable, PartialEq)]
+pub enum AddressStatus {
+    Verified,
+    Unverified,
+}
+
+// The skipped fields are only there to be left out of the schema.
+#[allow(dead_code)]
+#[derive(Serialize, KeyPathable)]
+pub struct Cached {
+    #[serde(rename = "_id")]
+    id: i32,
+    #[serde(skip_serializing)]
+    cache: String,
+    #[serde(skip)]
+    tmp: i32,
+    level: Level,
+}
+
+// Serializes as the variant name despite the integer repr.
+#[derive(Serialize, KeyPathable)]
+#[repr(u8)]
+pub enum Level {
+    Low = 1,
+}
+
+fn address_schema() -> bson::Document {
+    doc! {
+        "bsonType": "object",
+        "required": ["City", "Kind", "Status"],
+        "properties": {
+            "City": { "bsonType": "string" },
+            "Kind": {},
+            "Status": { "bsonType": "string" }
+        }
+    }
+}
+
+#[test]
+fn derived_json_schema_test() {
+    assert_eq!(
+        Person::json_schema(),
+        doc! {
+            "bsonType": "object",
+            "required": ["_id", "ProfileId", "Time", "Age", "Address", "Attempts"],
+            "properties": {
+                "_id": { "bsonType": "objectId" },
+                "ProfileId": {},
+                "Time": { "bsonType": "date" },
+                "Age": { "bsonType": "int" },
+                "Address": address_schema(),
+                "Nickname": { "bsonType": ["string", "null"] },
+                "Tags": {
+                    "bsonType": "array",
+                    "items": { "bsonType": "string" }
+                },
+                "Attempts": {
+                    "bsonType": "object",
+                    "additionalProperties": {
+                        "bsonType": "array",
+                        "items": { "bsonType": "long" }
+                    }
+                }
+            }
+        }
+    );
+}
+
+#[test]
+fn json_schema_filter_test() {
+    let filter = FilterBuilder::<Address>::new().json_schema().try_build();
+
+    assert_eq!(filter.unwrap(), doc! { "$jsonSchema": address_schema() });
+}
+
+#[test]
+fn repeated_json_schema_test() {
+    let errors = FilterBuilder::<Address>::new()
+        .json_schema()
+        .json_schema()
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors.to_string(), "$jsonSchema: duplicate operator");
+
+    let filter = FilterBuilder::<Address>::new()
+        .mode(FilterMode::Lenient)
+        .json_schema()
+        .json_schema()
+        .try_build();
+
+    assert_eq!(
+        filter.unwrap(),
+        doc! {
+            "$jsonSchema": address_schema(),
+            "$and": [{ "$jsonSchema": address_schema() }]
+        }
+    );
+}
+
+#[test]
+fn skipped_fields_are_left_out_test() {
+    assert_eq!(
+        Cached::json_schema(),
+        doc! {
+            "bsonType": "object",
+            "required": ["_id", "level"],
+            "properties": {
+                "_id": { "bsonType": "int" },
+                "level": {}
+            }
+        }
+    );
+
+    let cached = Cached {
+        id: 1,
+        cache: "stale".to_string(),
+        tmp: 2,
+        level: Level::Low,
+    };
+    let matcher = Matcher::new(FilterBuilder::<Cached>::new().json_schema());
+
+    assert!(matcher.unwrap().matches(&cached));
+}
@@ -0,0 +1,50 @@
+use bson::doc;
+use mqb_core::{kp::KeyPathableAsRoot, Meta, SortBuilder};
+use mqb_macro::KeyPathable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Article {
+    title: String,
+    published_at: i64,
+    stats: Stats,
+}
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    view_count: i64,
+}
+
+#[test]
+fn asc_desc_test() {
+    let sort = SortBuilder::<Article>::new()
+        .desc(Article::kp().stats().view_count())
+        .asc(Article::kp().title())
+        .build();
+
+    assert_eq!(
+        sort,
+        doc! {
+            "stats.viewCount": -1,
+            "title": 1
+        }
+    );
+}
+
+#[test]
+fn text_score_meta_test() {
+    let sort = SortBuilder::<Article>::new()
+        .meta("score", Meta::TextScore)
+        .desc(Article::kp().published_at())
+        .build();
+
+    assert_eq!(
+        sort,
+        doc! {
+            "score": { "$meta": "textScore" },
+            "publishedAt": -1
+        }
+    );
+}
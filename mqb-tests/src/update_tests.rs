@@ -2,7 +2,10 @@ use std::collections::HashMap;
 
 use bson::{doc, oid::ObjectId};
 use chrono::{DateTime, Utc};
-use mqb_core::{kp::KeyPathableAsRoot, UpdateBuilder};
+use mqb_core::{
+    kp::KeyPathableAsRoot, ErrorCause, FilterBuilder, PushModifiers, PushSort,
+    SortBuilder, UpdateBuilder,
+};
 use mqb_macro::KeyPathable;
 use serde::{Deserialize, Serialize};
 
@@ -21,6 +24,33 @@ pub struct Person {
     pub age: i32,
 
     pub attempts: HashMap<String, Vec<i32>>,
+
+    pub nickname: Option<String>,
+
+    pub alias: String,
+
+    pub rating: f64,
+
+    pub visits: Vec<Visit>,
+
+    pub planned_visits: Vec<Visit>,
+
+    #[serde(serialize_with = "upper_case")]
+    pub codes: Vec<String>,
+}
+
+fn upper_case<S: serde::Serializer>(
+    codes: &[String],
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    s.collect_seq(codes.iter().map(|code| code.to_uppercase()))
+}
+
+#[derive(Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "PascalCase")]
+pub struct Visit {
+    pub place: String,
+    pub duration: i32,
 }
 
 #[test]
@@ -165,11 +195,12 @@ fn test_combined_array_operations() {
 
 #[test]
 fn test_all_operations_combined() {
+    let oid = ObjectId::new();
     let profile_id = ObjectId::new();
 
     let update = UpdateBuilder::<Person>::new()
         // Set operations
-        .set(Person::kp().age(), 30)
+        .set(Person::kp().id(), oid)
         .set_on_insert(Person::kp().profile_id(), profile_id)
         // Array operations
         .push(Person::kp().attempts().key("recent".to_string()), 100)
@@ -183,7 +214,7 @@ fn test_all_operations_combined() {
 
     let expected = doc! {
         "$set": {
-            "Age": 30
+            "_id": oid
         },
         "$setOnInsert": {
             "ProfileId": profile_id.to_hex()
@@ -199,7 +230,7 @@ fn test_all_operations_combined() {
             }
         },
         "$inc": {
-            "Age": 1_i64
+            "Age": 1
         },
         "$currentDate": {
             "Time": true
@@ -208,3 +239,539 @@ fn test_all_operations_combined() {
 
     assert_eq!(update, expected);
 }
+
+#[test]
+fn test_conflicting_operators_on_same_path() {
+    let errors = UpdateBuilder::<Person>::new()
+        .set(Person::kp().age(), 30)
+        .inc(Person::kp().age(), 1)
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path(), Some("Age"));
+    assert_eq!(errors[0].operator(), "$inc");
+    assert!(matches!(
+        errors[0].cause(),
+        ErrorCause::Conflict { path, operator } if path == "Age" && operator == "$set"
+    ));
+}
+
+#[test]
+fn test_conflicting_parent_and_child_paths() {
+    let errors = UpdateBuilder::<Person>::new()
+        .set(Person::kp().attempts(), HashMap::new())
+        .push(Person::kp().attempts().key("recent".to_string()), 1)
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Attempts.recent $push: conflicts with Attempts $set"
+    );
+}
+
+#[test]
+fn test_sibling_paths_do_not_conflict() {
+    let update = UpdateBuilder::<Person>::new()
+        .push(Person::kp().attempts().key("a".to_string()), 1)
+        .push(Person::kp().attempts().key("ab".to_string()), 2)
+        .add_to_set(Person::kp().attempts().key("b".to_string()), 3)
+        .try_build();
+
+    assert!(update.is_ok());
+}
+
+#[test]
+fn test_unset() {
+    let update = UpdateBuilder::<Person>::new()
+        .unset(Person::kp().nickname())
+        .unset(Person::kp().attempts().key("stale".to_string()))
+        .try_build()
+        .unwrap();
+
+    let expected = doc! {
+        "$unset": {
+            "Nickname": "",
+            "Attempts.stale": ""
+        }
+    };
+
+    assert_eq!(update, expected);
+}
+
+#[test]
+fn test_set_none() {
+    let update = UpdateBuilder::<Person>::new()
+        .set(Person::kp().nickname(), None)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(update, doc! { "$set": { "Nickname": null } });
+
+    let update = UpdateBuilder::<Person>::new()
+        .set_or_unset(Person::kp().nickname(), None)
+        .set(Person::kp().age(), 30)
+        .try_build()
+        .unwrap();
+
+    let expected = doc! {
+        "$unset": {
+            "Nickname": ""
+        },
+        "$set": {
+            "Age": 30
+        }
+    };
+
+    assert_eq!(update, expected);
+
+    let update = UpdateBuilder::<Person>::new()
+        .set_or_unset(Person::kp().nickname(), Some("Al".to_string()))
+        .try_build()
+        .unwrap();
+
+    assert_eq!(update, doc! { "$set": { "Nickname": "Al" } });
+}
+
+#[test]
+fn test_arithmetic_operators() {
+    let now = Utc::now();
+
+    let update = UpdateBuilder::<Person>::new()
+        .inc(Person::kp().rating(), 0.5)
+        .mul(Person::kp().age(), 2)
+        .max(Person::kp().time(), now)
+        .try_build()
+        .unwrap();
+
+    let expected = doc! {
+        "$inc": {
+            "Rating": 0.5
+        },
+        "$mul": {
+            "Age": 2
+        },
+        "$max": {
+            "Time": now
+        }
+    };
+
+    assert_eq!(update, expected);
+}
+
+#[test]
+fn test_repeated_arithmetic_is_an_error() {
+    let errors = UpdateBuilder::<Person>::new()
+        .inc(Person::kp().age(), 1)
+        .inc(Person::kp().age(), 2)
+        .max(Person::kp().rating(), 4.0)
+        .max(Person::kp().rating(), 5.0)
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Age $inc: duplicate operator; Rating $max: duplicate operator"
+    );
+}
+
+#[test]
+fn test_repeated_set_and_rename_are_errors() {
+    let errors = UpdateBuilder::<Person>::new()
+        .set(Person::kp().rating(), 1.0)
+        .set(Person::kp().rating(), 2.0)
+        .set_on_insert(Person::kp().age(), 1)
+        .set_on_insert(Person::kp().age(), 2)
+        .rename(Person::kp().alias(), Person::kp().nickname())
+        .rename(Person::kp().alias(), Person::kp().nickname())
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Rating $set: duplicate operator; Age $setOnInsert: duplicate \
+         operator; Alias $rename: duplicate operator"
+    );
+}
+
+#[test]
+fn test_min_serializes_through_key_path() {
+    let profile_id = ObjectId::new();
+
+    let update = UpdateBuilder::<Person>::new()
+        .min(Person::kp().profile_id(), profile_id)
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        update,
+        doc! { "$min": { "ProfileId": profile_id.to_hex() } }
+    );
+}
+
+#[test]
+fn test_rename() {
+    let update = UpdateBuilder::<Person>::new()
+        .rename(Person::kp().alias(), Person::kp().nickname())
+        .try_build()
+        .unwrap();
+
+    assert_eq!(update, doc! { "$rename": { "Alias": "Nickname" } });
+}
+
+#[test]
+fn test_rename_target_conflicts() {
+    let errors = UpdateBuilder::<Person>::new()
+        .rename(Person::kp().alias(), Person::kp().nickname())
+        .set(Person::kp().nickname(), None)
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Nickname $set: conflicts with Nickname $rename"
+    );
+}
+
+#[test]
+fn test_pull_operators() {
+    let update = UpdateBuilder::<Person>::new()
+        .pull(Person::kp().attempts().key("scores".to_string()), 0)
+        .pull_where(Person::kp().visits(), |f: FilterBuilder<Visit>| {
+            f.eq(Visit::kp().place(), "Lobby".to_string())
+                .lt(Visit::kp().duration(), 5)
+        })
+        .pull_all(Person::kp().attempts().key("old".to_string()), [1, 2])
+        .pull_all(Person::kp().attempts().key("old".to_string()), [3])
+        .try_build()
+        .unwrap();
+
+    let expected = doc! {
+        "$pull": {
+            "Attempts.scores": 0,
+            "Visits": {
+                "Place": { "$eq": "Lobby" },
+                "Duration": { "$lt": 5 }
+            }
+        },
+        "$pullAll": {
+            "Attempts.old": [1, 2, 3]
+        }
+    };
+
+    assert_eq!(update, expected);
+}
+
+#[test]
+fn test_pop() {
+    let update = UpdateBuilder::<Person>::new()
+        .pop_first(Person::kp().visits())
+        .pop_last(Person::kp().attempts().key("scores".to_string()))
+        .try_build()
+        .unwrap();
+
+    let expected = doc! {
+        "$pop": {
+            "Visits": -1,
+            "Attempts.scores": 1
+        }
+    };
+
+    assert_eq!(update, expected);
+}
+
+#[test]
+fn test_pull_values_use_field_serializer() {
+    let update = UpdateBuilder::<Person>::new()
+        .pull(Person::kp().codes(), "ab")
+        .pull_where_value(
+            Person::kp().attempts().key("scores".to_string()),
+            |e| e.gte(6),
+        )
+        .try_build()
+        .unwrap();
+
+    let expected = doc! {
+        "$pull": {
+            "Codes": "AB",
+            "Attempts.scores": { "$gte": 6 }
+        }
+    };
+
+    assert_eq!(update, expected);
+
+    let update = UpdateBuilder::<Person>::new()
+        .pull_all(Person::kp().codes(), ["cd", "ef"])
+        .try_build()
+        .unwrap();
+
+    assert_eq!(update, doc! { "$pullAll": { "Codes": ["CD", "EF"] } });
+}
+
+#[test]
+fn test_text_in_element_filters_is_an_error() {
+    let errors = UpdateBuilder::<Person>::new()
+        .pull_where(Person::kp().visits(), |f: FilterBuilder<Visit>| {
+            f.text("lobby", Default::default())
+        })
+        .set(Person::kp().planned_visits().filtered("v").place(), "Hall")
+        .array_filter(
+            Person::kp().planned_visits(),
+            "v",
+            |f: FilterBuilder<Visit>| f.text("lobby", Default::default()),
+        )
+        .try_build_with_array_filters()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Visits $text: only allowed at the top level of a query or in \
+         $and/$or; v $text: only allowed at the top level of a query or in \
+         $and/$or; PlannedVisits.$[v].Place $set: no array filter for \
+         identifier 'v'"
+    );
+}
+
+#[test]
+fn test_repeated_pull_is_an_error() {
+    let errors = UpdateBuilder::<Person>::new()
+        .pull(Person::kp().attempts().key("scores".to_string()), 0)
+        .pull(Person::kp().attempts().key("scores".to_string()), 1)
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Attempts.scores $pull: duplicate operator"
+    );
+}
+
+#[test]
+fn test_push_with_modifiers() {
+    let update = UpdateBuilder::<Person>::new()
+        .push_with(
+            Person::kp().visits(),
+            [Visit {
+                place: "Lobby".to_string(),
+                duration: 3,
+            }],
+            PushModifiers::new()
+                .position(0)
+                .sort(SortBuilder::new().desc(Visit::kp().duration()))
+                .slice(10),
+        )
+        .push_with(
+            Person::kp().attempts().key("scores".to_string()),
+            [7, 9],
+            PushModifiers::new().sort(PushSort::Ascending).slice(-5),
+        )
+        .try_build()
+        .unwrap();
+
+    let expected = doc! {
+        "$push": {
+            "Visits": {
+                "$each": [{ "Place": "Lobby", "Duration": 3 }],
+                "$position": 0_i64,
+                "$sort": { "Duration": -1 },
+                "$slice": 10_i64
+            },
+            "Attempts.scores": {
+                "$each": [7, 9],
+                "$sort": 1,
+                "$slice": -5_i64
+            }
+        }
+    };
+
+    assert_eq!(update, expected);
+}
+
+#[test]
+fn test_push_many_and_add_to_set_many() {
+    let update = UpdateBuilder::<Person>::new()
+        .push(Person::kp().attempts().key("scores".to_string()), 1)
+        .push_many(Person::kp().attempts().key("scores".to_string()), [2, 3])
+        .add_to_set_many(
+            Person::kp().attempts().key("unique".to_string()),
+            vec![4, 5],
+        )
+        .try_build()
+        .unwrap();
+
+    let expected = doc! {
+        "$push": {
+            "Attempts.scores": { "$each": [1, 2, 3] }
+        },
+        "$addToSet": {
+            "Attempts.unique": { "$each": [4, 5] }
+        }
+    };
+
+    assert_eq!(update, expected);
+}
+
+#[test]
+fn test_push_modifiers_are_not_merged() {
+    let errors = UpdateBuilder::<Person>::new()
+        .push(Person::kp().attempts().key("scores".to_string()), 1)
+        .push_with(
+            Person::kp().attempts().key("scores".to_string()),
+            [2],
+            PushModifiers::new().slice(3),
+        )
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Attempts.scores $push: duplicate operator"
+    );
+}
+
+#[test]
+fn test_positional_paths() {
+    let update = UpdateBuilder::<Person>::new()
+        .set(
+            Person::kp().visits().first_match().place(),
+            "Hall".to_string(),
+        )
+        .try_build()
+        .unwrap();
+
+    assert_eq!(update, doc! { "$set": { "Visits.$.Place": "Hall" } });
+
+    let (update, array_filters) = UpdateBuilder::<Person>::new()
+        .inc(Person::kp().visits().all().duration(), 1)
+        .set(
+            Person::kp().visits().filtered("long").place(),
+            "Hall".to_string(),
+        )
+        .array_filter(
+            Person::kp().visits(),
+            "long",
+            |f: FilterBuilder<Visit>| f.gte(Visit::kp().duration(), 60),
+        )
+        .try_build_with_array_filters()
+        .unwrap();
+
+    let expected = doc! {
+        "$inc": {
+            "Visits.$[].Duration": 1
+        },
+        "$set": {
+            "Visits.$[long].Place": "Hall"
+        }
+    };
+
+    assert_eq!(update, expected);
+    assert_eq!(
+        array_filters,
+        vec![doc! { "long.Duration": { "$gte": 60 } }]
+    );
+}
+
+#[test]
+fn test_positional_paths_conflict_with_any_element() {
+    let errors = UpdateBuilder::<Person>::new()
+        .inc(Person::kp().visits().all().duration(), 1)
+        .set(Person::kp().visits().filtered("long").duration(), 60)
+        .array_filter(
+            Person::kp().visits(),
+            "long",
+            |f: FilterBuilder<Visit>| f.gte(Visit::kp().duration(), 60),
+        )
+        .try_build_with_array_filters()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Visits.$[long].Duration $set: conflicts with Visits.$[].Duration $inc"
+    );
+
+    let errors = UpdateBuilder::<Person>::new()
+        .set(
+            Person::kp().visits().first_match().place(),
+            "Hall".to_string(),
+        )
+        .push(
+            Person::kp().visits(),
+            Visit {
+                place: "Lobby".to_string(),
+                duration: 3,
+            },
+        )
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Visits $push: conflicts with Visits.$.Place $set"
+    );
+}
+
+#[test]
+fn test_array_filters_on_scalar_elements() {
+    let (update, array_filters) = UpdateBuilder::<Person>::new()
+        .set(Person::kp().codes().filtered("code"), "XY".to_string())
+        .array_filter_value(Person::kp().codes(), "code", |e| e.eq("ab"))
+        .try_build_with_array_filters()
+        .unwrap();
+
+    assert_eq!(update, doc! { "$set": { "Codes.$[code]": "XY" } });
+    assert_eq!(array_filters, vec![doc! { "code": { "$eq": "AB" } }]);
+}
+
+#[test]
+fn test_array_filters_belong_to_their_array() {
+    let errors = UpdateBuilder::<Person>::new()
+        .set(
+            Person::kp().planned_visits().filtered("long").duration(),
+            60,
+        )
+        .array_filter(
+            Person::kp().visits(),
+            "long",
+            |f: FilterBuilder<Visit>| f.gte(Visit::kp().duration(), 60),
+        )
+        .try_build_with_array_filters()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "PlannedVisits.$[long].Duration $set: identifier 'long' filters the \
+         elements of 'Visits'"
+    );
+}
+
+#[test]
+fn test_array_filters_must_match_identifiers() {
+    let errors = UpdateBuilder::<Person>::new()
+        .set(Person::kp().visits().filtered("short").duration(), 0)
+        .array_filter(
+            Person::kp().visits(),
+            "long",
+            |f: FilterBuilder<Visit>| f.gte(Visit::kp().duration(), 60),
+        )
+        .try_build_with_array_filters()
+        .unwrap_err();
+
+    assert_eq!(
+        errors.to_string(),
+        "Visits.$[short].Duration $set: no array filter for identifier \
+         'short'; long arrayFilters: the identifier is not used by the update"
+    );
+
+    let errors = UpdateBuilder::<Person>::new()
+        .set(Person::kp().visits().filtered("long").duration(), 0)
+        .array_filter(
+            Person::kp().visits(),
+            "long",
+            |f: FilterBuilder<Visit>| f.gte(Visit::kp().duration(), 60),
+        )
+        .try_build()
+        .unwrap_err();
+
+    assert_eq!(errors[0].operator(), "arrayFilters");
+}
@@ -0,0 +1,604 @@
+use bson::doc;
+use chrono::{DateTime, TimeZone, Utc};
+use mqb_core::{
+    kp::KeyPathableAsRoot, EvalError, FilterBuilder, PushModifiers, PushSort,
+    SortBuilder, UpdateBuilder, Updater,
+};
+use mqb_macro::KeyPathable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, KeyPathable)]
+#[serde(rename_all = "camelCase")]
+pub struct Player {
+    #[serde(rename = "_id")]
+    id: i32,
+    name: String,
+    level: i32,
+    score: i64,
+    badges: Vec<String>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    seen_at: DateTime<Utc>,
+    origin: Option<String>,
+}
+
+fn player() -> Player {
+    Player {
+        id: 7,
+        name: "Ada".to_string(),
+        level: 3,
+        score: 10,
+        badges: vec!["gold".to_string()],
+        seen_at: Utc.timestamp_millis_opt(0).unwrap(),
+        origin: None,
+    }
+}
+
+#[test]
+fn test_apply_set_and_inc() {
+    let updater = Updater::new(
+        UpdateBuilder::<Player>::new()
+            .set(Player::kp().name(), "Grace".to_string())
+            .inc(Player::kp().level(), 2)
+            .inc(Player::kp().score(), -4),
+    )
+    .unwrap();
+
+    let mut player = player();
+    updater.apply(&mut player).unwrap();
+
+    assert_eq!(player.name, "Grace");
+    assert_eq!(player.level, 5);
+    assert_eq!(player.score, 6);
+}
+
+#[test]
+fn test_set_on_insert_requires_upsert() {
+    let update = || {
+        UpdateBuilder::<Player>::new()
+            .set_on_insert(Player::kp().origin(), Some("import".to_string()))
+    };
+
+    let mut player = player();
+    Updater::new(update()).unwrap().apply(&mut player).unwrap();
+    assert_eq!(player.origin, None);
+
+    Updater::new(update())
+        .unwrap()
+        .upsert(true)
+        .apply(&mut player)
+        .unwrap();
+    assert_eq!(player.origin, Some("import".to_string()));
+}
+
+#[test]
+fn test_push_and_add_to_set() {
+    let push = Updater::new(
+        UpdateBuilder::<Player>::new()
+            .push(Player::kp().badges(), "silver".to_string()),
+    )
+    .unwrap();
+    let add_to_set = Updater::new(
+        UpdateBuilder::<Player>::new()
+            .add_to_set(Player::kp().badges(), "gold".to_string())
+            .add_to_set(Player::kp().badges(), "bronze".to_string()),
+    )
+    .unwrap();
+
+    let mut player = player();
+    push.apply(&mut player).unwrap();
+    add_to_set.apply(&mut player).unwrap();
+
+    assert_eq!(player.badges, vec!["gold", "silver", "bronze"]);
+}
+
+#[test]
+fn test_current_date_uses_clock() {
+    let now = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+    let updater = Updater::new(
+        UpdateBuilder::<Player>::new().current_date(Player::kp().seen_at()),
+    )
+    .unwrap()
+    .clock(move || now.into());
+
+    let mut player = player();
+    updater.apply(&mut player).unwrap();
+
+    assert_eq!(player.seen_at, now);
+}
+
+#[test]
+fn test_apply_to_document_creates_missing_paths() {
+    let updater = Updater::new(
+        UpdateBuilder::<Player>::new()
+            .inc(Player::kp().score(), 5)
+            .push(Player::kp().badges(), "gold".to_string()),
+    )
+    .unwrap();
+
+    let mut document = doc! { "_id": 1 };
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(
+        document,
+        doc! { "_id": 1, "score": 5_i64, "badges": ["gold"] }
+    );
+}
+
+#[test]
+fn test_decimal_arithmetic() {
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Account {
+        balance: bson::Decimal128,
+        fee: bson::Decimal128,
+    }
+
+    let decimal = |value: &str| value.parse::<bson::Decimal128>().unwrap();
+
+    let updater = Updater::new(
+        UpdateBuilder::<Account>::new()
+            .inc(Account::kp().balance(), decimal("0.75"))
+            .mul(Account::kp().fee(), decimal("1.5")),
+    )
+    .unwrap();
+
+    let mut document = doc! { "balance": decimal("10.25") };
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(
+        document,
+        doc! { "balance": decimal("11.00"), "fee": decimal("0") }
+    );
+
+    updater.apply_to_document(&mut document).unwrap();
+    document.insert("fee", decimal("2"));
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(
+        document,
+        doc! { "balance": decimal("12.50"), "fee": decimal("3.0") }
+    );
+}
+
+#[test]
+fn test_decimal_arithmetic_rounds_to_34_digits() {
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    pub struct Account {
+        balance: bson::Decimal128,
+    }
+
+    let decimal = |value: &str| value.parse::<bson::Decimal128>().unwrap();
+    let apply = |update: UpdateBuilder<Account>, balance: &str| {
+        let mut document = doc! { "balance": decimal(balance) };
+        Updater::new(update)
+            .unwrap()
+            .apply_to_document(&mut document)
+            .map(|_| document)
+    };
+
+    let large = "1234567890123456789012345678901234";
+    let document = apply(
+        UpdateBuilder::new().mul(Account::kp().balance(), decimal(large)),
+        large,
+    )
+    .unwrap();
+    assert_eq!(
+        document,
+        doc! { "balance": decimal("1.524157875323883675049535156256667E+66") }
+    );
+
+    let document = apply(
+        UpdateBuilder::new().inc(Account::kp().balance(), decimal("1E-6000")),
+        "-1E+6000",
+    )
+    .unwrap();
+    assert_eq!(
+        document,
+        doc! { "balance": decimal("-1.000000000000000000000000000000000E+6000") }
+    );
+
+    let error = apply(
+        UpdateBuilder::new().mul(Account::kp().balance(), decimal("9E+6000")),
+        "9E+6000",
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "$mul: balance: decimal overflow");
+}
+
+#[test]
+fn test_int32_inc_promotes_on_overflow() {
+    let updater = Updater::new(
+        UpdateBuilder::<Player>::new().inc(Player::kp().level(), 1),
+    )
+    .unwrap();
+
+    let mut document = doc! { "level": i32::MAX };
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(document, doc! { "level": i32::MAX as i64 + 1 });
+}
+
+#[test]
+fn test_failed_update_leaves_document_untouched() {
+    let updater = Updater::new(
+        UpdateBuilder::<Player>::new()
+            .set(Player::kp().name(), "Grace".to_string())
+            .inc(Player::kp().score(), 1),
+    )
+    .unwrap();
+
+    let mut document = doc! { "name": "Ada", "score": "high" };
+    let result = updater.apply_to_document(&mut document);
+
+    assert!(matches!(result, Err(EvalError::Invalid { .. })));
+    assert_eq!(document, doc! { "name": "Ada", "score": "high" });
+}
+
+#[test]
+fn test_id_is_immutable() {
+    let updater =
+        Updater::new(UpdateBuilder::<Player>::new().set(Player::kp().id(), 8))
+            .unwrap();
+
+    let mut player = player();
+    assert!(updater.apply(&mut player).is_err());
+    assert_eq!(player.id, 7);
+}
+
+#[test]
+fn test_unset_removes_field() {
+    let updater = Updater::new(
+        UpdateBuilder::<Player>::new()
+            .set_or_unset(Player::kp().origin(), None),
+    )
+    .unwrap();
+
+    let mut document = doc! { "_id": 7, "origin": "import" };
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(document, doc! { "_id": 7 });
+
+    let mut player = player();
+    player.origin = Some("import".to_string());
+    updater.apply(&mut player).unwrap();
+
+    assert_eq!(player.origin, None);
+}
+
+#[test]
+fn test_mul_min_and_max() {
+    let updater = Updater::new(
+        UpdateBuilder::<Player>::new()
+            .mul(Player::kp().score(), 3)
+            .min(Player::kp().level(), 5)
+            .max(Player::kp().name(), "Zed".to_string()),
+    )
+    .unwrap();
+
+    let mut player = player();
+    updater.apply(&mut player).unwrap();
+
+    assert_eq!(player.score, 30);
+    assert_eq!(player.level, 3);
+    assert_eq!(player.name, "Zed");
+
+    let mut document = doc! {};
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(document, doc! { "score": 0_i64, "level": 5, "name": "Zed" });
+}
+
+#[test]
+fn test_inc_on_double() {
+    let updater = Updater::new(
+        UpdateBuilder::<Player>::new().inc(Player::kp().level(), 1),
+    )
+    .unwrap();
+
+    let mut document = doc! { "level": 1.5 };
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(document, doc! { "level": 2.5 });
+}
+
+#[test]
+fn test_rename_moves_value() {
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Legacy {
+        title: String,
+        name: Option<String>,
+    }
+
+    let updater = Updater::new(
+        UpdateBuilder::<Legacy>::new()
+            .rename(Legacy::kp().title(), Legacy::kp().name()),
+    )
+    .unwrap();
+
+    let legacy = Legacy {
+        title: "Ada".to_string(),
+        name: None,
+    };
+    let mut document = bson::to_document(&legacy).unwrap();
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(document, doc! { "name": "Ada" });
+}
+
+#[test]
+fn test_pull_and_pop() {
+    let mut player = player();
+    player.badges = ["gold", "silver", "bronze", "iron", "silver"]
+        .map(String::from)
+        .to_vec();
+
+    Updater::new(
+        UpdateBuilder::<Player>::new()
+            .pull(Player::kp().badges(), "silver".to_string()),
+    )
+    .unwrap()
+    .apply(&mut player)
+    .unwrap();
+    assert_eq!(player.badges, vec!["gold", "bronze", "iron"]);
+
+    Updater::new(
+        UpdateBuilder::<Player>::new()
+            .pull_all(Player::kp().badges(), ["iron".to_string()]),
+    )
+    .unwrap()
+    .apply(&mut player)
+    .unwrap();
+    assert_eq!(player.badges, vec!["gold", "bronze"]);
+
+    Updater::new(
+        UpdateBuilder::<Player>::new().pop_first(Player::kp().badges()),
+    )
+    .unwrap()
+    .apply(&mut player)
+    .unwrap();
+    assert_eq!(player.badges, vec!["bronze"]);
+}
+
+#[test]
+fn test_pull_where_value_removes_matching_elements() {
+    let mut player = player();
+    player.badges = ["gold", "silver", "bronze", "iron"]
+        .map(String::from)
+        .to_vec();
+
+    Updater::new(
+        UpdateBuilder::<Player>::new()
+            .pull_where_value(Player::kp().badges(), |e| {
+                e.r#in(["silver", "iron"])
+            }),
+    )
+    .unwrap()
+    .apply(&mut player)
+    .unwrap();
+    assert_eq!(player.badges, vec!["gold", "bronze"]);
+}
+
+#[test]
+fn test_array_filters_on_scalar_elements() {
+    let mut player = player();
+    player.badges = ["gold", "silver", "gold"].map(String::from).to_vec();
+
+    Updater::new(
+        UpdateBuilder::<Player>::new()
+            .set(
+                Player::kp().badges().filtered("gold"),
+                "platinum".to_string(),
+            )
+            .array_filter_value(Player::kp().badges(), "gold", |e| {
+                e.eq("gold")
+            }),
+    )
+    .unwrap()
+    .apply(&mut player)
+    .unwrap();
+    assert_eq!(player.badges, vec!["platinum", "silver", "platinum"]);
+}
+
+#[test]
+fn test_array_filters_conflict_on_shared_elements() {
+    let update = |second: &'static str| {
+        Updater::new(
+            UpdateBuilder::<Player>::new()
+                .set(Player::kp().badges().filtered("a"), "first".to_string())
+                .set(Player::kp().badges().filtered("b"), "second".to_string())
+                .array_filter_value(Player::kp().badges(), "a", |e| {
+                    e.eq("gold")
+                })
+                .array_filter_value(Player::kp().badges(), "b", move |e| {
+                    e.eq(second)
+                }),
+        )
+        .unwrap()
+    };
+
+    let mut player = player();
+    player.badges = ["gold", "silver"].map(String::from).to_vec();
+
+    update("silver").apply(&mut player).unwrap();
+    assert_eq!(player.badges, vec!["first", "second"]);
+
+    player.badges = ["gold", "silver"].map(String::from).to_vec();
+
+    let error = update("gold").apply(&mut player).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "$set: Update created a conflict at 'badges.0'"
+    );
+    assert_eq!(player.badges, vec!["gold", "silver"]);
+}
+
+#[test]
+fn test_pull_where_removes_matching_subdocuments() {
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Cart {
+        items: Vec<Item>,
+    }
+
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Item {
+        sku: String,
+        quantity: i32,
+    }
+
+    let updater = Updater::new(
+        UpdateBuilder::<Cart>::new()
+            .pull_where(Cart::kp().items(), |f: FilterBuilder<Item>| {
+                f.lte(Item::kp().quantity(), 0)
+            }),
+    )
+    .unwrap();
+
+    let mut document = doc! {
+        "items": [
+            { "sku": "a", "quantity": 2 },
+            { "sku": "b", "quantity": 0 },
+            { "sku": "c", "quantity": -1 }
+        ]
+    };
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(document, doc! { "items": [{ "sku": "a", "quantity": 2 }] });
+}
+
+#[test]
+fn test_pull_where_with_or() {
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Cart {
+        items: Vec<Item>,
+    }
+
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Item {
+        sku: String,
+        quantity: i32,
+    }
+
+    let updater = Updater::new(UpdateBuilder::<Cart>::new().pull_where(
+        Cart::kp().items(),
+        |f: FilterBuilder<Item>| {
+            f.or([
+                FilterBuilder::new().eq(Item::kp().sku(), "a".to_string()),
+                FilterBuilder::new().lte(Item::kp().quantity(), 0),
+            ])
+        },
+    ))
+    .unwrap();
+
+    let mut cart = Cart {
+        items: vec![
+            Item {
+                sku: "a".to_string(),
+                quantity: 2,
+            },
+            Item {
+                sku: "b".to_string(),
+                quantity: 0,
+            },
+            Item {
+                sku: "c".to_string(),
+                quantity: 1,
+            },
+        ],
+    };
+    updater.apply(&mut cart).unwrap();
+
+    let skus = cart.items.iter().map(|item| &item.sku).collect::<Vec<_>>();
+    assert_eq!(skus, vec!["c"]);
+}
+
+#[test]
+fn test_push_keeps_recent_entries() {
+    let updater = Updater::new(UpdateBuilder::<Player>::new().push_with(
+        Player::kp().badges(),
+        ["d".to_string(), "e".to_string()],
+        PushModifiers::new().slice(-3),
+    ))
+    .unwrap();
+
+    let mut document = doc! { "badges": ["a", "b", "c"] };
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(document, doc! { "badges": ["c", "d", "e"] });
+}
+
+#[test]
+fn test_push_position_and_sort() {
+    let updater = Updater::new(UpdateBuilder::<Player>::new().push_with(
+        Player::kp().badges(),
+        ["x".to_string()],
+        PushModifiers::new().position(-1),
+    ))
+    .unwrap();
+
+    let mut document = doc! { "badges": ["a", "b"] };
+    updater.apply_to_document(&mut document).unwrap();
+    assert_eq!(document, doc! { "badges": ["a", "x", "b"] });
+
+    let updater = Updater::new(UpdateBuilder::<Player>::new().push_with(
+        Player::kp().badges(),
+        ["m".to_string()],
+        PushModifiers::new().sort(PushSort::Descending),
+    ))
+    .unwrap();
+
+    updater.apply_to_document(&mut document).unwrap();
+    assert_eq!(document, doc! { "badges": ["x", "m", "b", "a"] });
+}
+
+#[test]
+fn test_push_sorts_subdocuments_by_key_path() {
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Feed {
+        events: Vec<Event>,
+    }
+
+    #[derive(Serialize, Deserialize, KeyPathable)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Event {
+        name: String,
+        at: i64,
+    }
+
+    let updater = Updater::new(
+        UpdateBuilder::<Feed>::new().push_with(
+            Feed::kp().events(),
+            [Event {
+                name: "late".to_string(),
+                at: 5,
+            }],
+            PushModifiers::new()
+                .sort(SortBuilder::new().desc(Event::kp().at()))
+                .slice(2),
+        ),
+    )
+    .unwrap();
+
+    let mut document = doc! {
+        "events": [
+            { "name": "first", "at": 1_i64 },
+            { "name": "second", "at": 3_i64 }
+        ]
+    };
+    updater.apply_to_document(&mut document).unwrap();
+
+    assert_eq!(
+        document,
+        doc! {
+            "events": [
+                { "name": "late", "at": 5_i64 },
+                { "name": "second", "at": 3_i64 }
+            ]
+        }
+    );
+}